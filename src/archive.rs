@@ -0,0 +1,146 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font};
+use crate::credits::credits;
+use crate::licenses::{notice, LicenseEntry};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+/// The fonts that an archive has.
+enum Scope<'a> {
+    All,
+    Encoding(&'a str),
+    /// All encodings and sizes of the family.
+    Family(&'a str),
+}
+
+/// The archive name (without extension).
+///
+/// Templates link to archives using the same naming scheme.
+fn archive_name(scope: Scope) -> String {
+    match scope {
+        Scope::All => "firefly-fonts".to_string(),
+        Scope::Encoding(encoding) => format!("firefly-fonts-{encoding}"),
+        Scope::Family(family) => format!("firefly-fonts-family-{family}"),
+    }
+}
+
+/// The `manifest.json` file included into each archive.
+#[derive(Serialize)]
+struct Manifest<'a> {
+    name: &'a str,
+    fonts: Vec<Entry<'a>>,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    /// Path to the font file inside the archive.
+    path: String,
+    #[serde(flatten)]
+    font: &'a Font,
+}
+
+/// Write a zip archive with fonts for each encoding, for each family, and one with all fonts.
+///
+/// Each archive includes license texts for all included fonts,
+/// `NOTICE.txt` with the licenses of each font and what they require,
+/// `CREDITS` for games to include, and `manifest.json` describing the fonts.
+///
+/// Returns how many archives were updated.
+pub(crate) fn build_archives(
+    out_dir: &Path,
+    base_url: &str,
+    licenses: &[LicenseEntry],
+    fonts: &[Font],
+    cache: &BuildCache,
+) -> Result<usize> {
+    std::fs::create_dir_all(out_dir).context("create archives dir")?;
+    let fonts = unique_files(fonts);
+    let encodings: BTreeSet<&str> = fonts.iter().map(|f| f.encoding).collect();
+    let mut archives: Vec<(String, Vec<&Font>)> = encodings
+        .into_iter()
+        .map(|encoding| {
+            let fonts = fonts.iter().filter(|f| f.encoding == encoding).copied();
+            (archive_name(Scope::Encoding(encoding)), fonts.collect())
+        })
+        .collect();
+    let families: BTreeSet<&str> = fonts.iter().map(|f| f.family.as_str()).collect();
+    for family in families {
+        let family_fonts = fonts.iter().filter(|f| f.family == family).copied();
+        archives.push((archive_name(Scope::Family(family)), family_fonts.collect()));
+    }
+    archives.push((archive_name(Scope::All), fonts));
+
+    let updated = AtomicUsize::new(0);
+    archives
+        .par_iter()
+        .try_for_each(|(name, fonts)| -> Result<()> {
+            let files = archive_files(name, base_url, licenses, fonts)?;
+            let path = out_dir.join(format!("{name}.zip"));
+            if !cache.is_fresh(&path, hash_of(&files)) {
+                write_zip(&path, &files).context("write archive")?;
+                updated.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        })?;
+    Ok(updated.into_inner())
+}
+
+/// Paths and contents of all files to put into the archive.
+fn archive_files(
+    name: &str,
+    base_url: &str,
+    licenses: &[LicenseEntry],
+    fonts: &[&Font],
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut entries = Vec::new();
+    for font in fonts {
+        let path = format!("{}/{}.fff", font.encoding, font.name());
+        files.push((format!("{name}/{path}"), font.data.to_bytes()?));
+        entries.push(Entry { path, font });
+    }
+
+    let used: BTreeSet<&str> = fonts
+        .iter()
+        .flat_map(|f| f.licenses.iter().map(|l| &*l.spdx))
+        .collect();
+    for license in licenses.iter().filter(|l| used.contains(l.info.spdx)) {
+        let path = format!("{name}/LICENSES/{}.txt", license.info.spdx);
+        files.push((path, license.text.clone().into_bytes()));
+    }
+    let notice = notice(fonts, base_url).context("write notice")?;
+    files.push((format!("{name}/NOTICE.txt"), notice.into_bytes()));
+    files.push((format!("{name}/CREDITS"), credits(fonts).into_bytes()));
+
+    let manifest = Manifest {
+        name,
+        fonts: entries,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).context("serialize manifest")?;
+    files.push((format!("{name}/manifest.json"), manifest));
+    Ok(files)
+}
+
+fn write_zip(path: &Path, files: &[(String, Vec<u8>)]) -> Result<()> {
+    let file = std::fs::File::create(path).context("create file")?;
+    let mut zip = ZipWriter::new(file);
+    // The fixed modification time keeps archives the same for the same inputs.
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default())
+        .unix_permissions(0o644);
+    for (name, content) in files {
+        zip.start_file(name.as_str(), options)
+            .context("start file")?;
+        zip.write_all(content).context("write file")?;
+    }
+    zip.finish().context("finish archive")?;
+    Ok(())
+}
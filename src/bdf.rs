@@ -0,0 +1,272 @@
+use crate::bitmap::{BitmapFont, Glyph};
+use crate::extra_fonts::{License, Provenance};
+use crate::fff::Fff;
+use crate::generator::encoding_index;
+use anyhow::{bail, Context, Result};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// X11 misc-fixed fonts for encodings not covered by embedded-graphics.
+///
+/// All files in `bdf/` are trimmed to the characters the catalog needs.
+/// Versions are from the `$ucs-fonts$` comments of the files.
+static FILES: &[(&str, Provenance)] = &[
+    (
+        "6x13",
+        misc("1.115, 2009-04-06", "converted from bdf/6x13.bdf"),
+    ),
+    (
+        "8x13",
+        misc("1.57, 2006-01-05", "converted from bdf/8x13.bdf"),
+    ),
+    (
+        "9x15",
+        misc("1.83, 2009-04-06", "converted from bdf/9x15.bdf"),
+    ),
+    (
+        "10x20",
+        misc("1.91, 2009-04-06", "converted from bdf/10x20.bdf"),
+    ),
+];
+
+/// Misc-fixed files that embedded-graphics fonts are made from, with their eg family.
+///
+/// embedded-graphics fonts have only the characters of their encodings, so glyphs
+/// that no eg encoding has (like Ґ of KOI8-U or ™ of Windows-1251) are taken
+/// from the files directly. Bold and italic files lack some math symbols,
+/// and for them the regular file of the same size is used.
+static EG_FILES: &[(&str, &str, &str)] = &[
+    ("eg", "4x6", "1.5, 2002-08-26"),
+    ("eg", "5x7", "1.38, 2006-01-05"),
+    ("eg", "5x8", "1.32, 2006-01-05"),
+    ("eg", "6x9", "1.28, 2006-01-05"),
+    ("eg", "6x10", "1.35, 2006-01-05"),
+    ("eg", "6x12", "1.32, 2008-06-26"),
+    ("eg", "6x13", "1.115, 2009-04-06"),
+    ("eg-bold", "6x13B", "1.26, 2006-01-05"),
+    ("eg-italic", "6x13O", "1.7, 2002-08-26"),
+    ("eg", "7x13", "1.52, 2008-04-19"),
+    ("eg-bold", "7x13B", "1.27, 2002-11-08"),
+    ("eg-italic", "7x13O", "1.9, 2002-11-08"),
+    ("eg", "7x14", "1.47, 2006-01-05"),
+    ("eg-bold", "7x14B", "1.25, 2003-07-26"),
+    ("eg", "8x13", "1.57, 2006-01-05"),
+    ("eg-bold", "8x13B", "1.28, 2002-11-15"),
+    ("eg-italic", "8x13O", "1.8, 2006-01-05"),
+    ("eg", "9x15", "1.83, 2009-04-06"),
+    ("eg-bold", "9x15B", "1.35, 2006-01-05"),
+    ("eg", "9x18", "1.53, 2009-04-06"),
+    ("eg-bold", "9x18B", "1.14, 2004-11-28"),
+    ("eg", "10x20", "1.91, 2009-04-06"),
+];
+
+/// A misc-fixed font of an embedded-graphics family, as a Unicode-indexed FFF.
+pub(crate) struct EgFile {
+    pub family: &'static str,
+    pub data: Arc<Fff>,
+    pub provenance: Provenance,
+}
+
+const fn misc(version: &'static str, conversion: &'static str) -> Provenance {
+    Provenance {
+        author: Cow::Borrowed("Markus Kuhn and X11 contributors"),
+        url: Cow::Borrowed("https://www.cl.cam.ac.uk/~mgk25/ucs-fonts.html"),
+        version: Some(Cow::Borrowed(version)),
+        conversion: Cow::Borrowed(conversion),
+    }
+}
+
+/// Encodings to generate from BDF fonts.
+///
+/// A font is generated only if it has glyphs for all characters of the encoding.
+static ENCODINGS: &[&str] = &["iso_8859_8", "iso_8859_6", "iso_8859_11"];
+
+pub(crate) static MISC_LICENSE: License = License {
+    spdx: Cow::Borrowed("LicenseRef-Public-Domain"),
+    url: None,
+    copyright: None,
+};
+
+/// Load a font in the Glyph Bitmap Distribution Format used by X11.
+pub(crate) fn read(path: &Path) -> Result<BitmapFont> {
+    let raw = std::fs::read_to_string(path).context("read BDF file")?;
+    parse(&raw)
+}
+
+/// Parse the BDF source.
+///
+/// Only what is needed for bitmap fonts is read: the font bounding box, which becomes
+/// the cell, and, for each glyph, the Unicode code point, the advance,
+/// the bounding box, and the bitmap. Glyphs without a code point (`ENCODING -1`) are skipped.
+pub(crate) fn parse(raw: &str) -> Result<BitmapFont> {
+    let mut bbox = None;
+    let mut glyphs = HashMap::new();
+    let mut lines = raw.lines();
+    let mut current: Option<char> = None;
+    let mut advance = None;
+    let mut glyph_bbox = None;
+    while let Some(line) = lines.next() {
+        let (keyword, args) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "FONTBOUNDINGBOX" => bbox = Some(parse_bbox(args)?),
+            "STARTCHAR" => {
+                current = None;
+                advance = None;
+                glyph_bbox = None;
+            }
+            "ENCODING" => {
+                let code: i64 = args.trim().parse().context("parse glyph encoding")?;
+                current = u32::try_from(code).ok().and_then(char::from_u32);
+            }
+            "DWIDTH" => {
+                let dx = args.split_whitespace().next().unwrap_or_default();
+                advance = Some(dx.parse().context("parse glyph advance")?);
+            }
+            "BBX" => glyph_bbox = Some(parse_bbox(args)?),
+            "BITMAP" => {
+                let Some((width, height, x_offset, y_offset)) = glyph_bbox else {
+                    bail!("glyph bitmap without a bounding box");
+                };
+                let mut rows = Vec::new();
+                for _ in 0..height {
+                    let Some(row) = lines.next() else {
+                        bail!("glyph bitmap is truncated");
+                    };
+                    rows.push(parse_hex(row.trim())?);
+                }
+                if let Some(c) = current {
+                    let glyph = Glyph {
+                        width,
+                        height,
+                        x_offset,
+                        y_offset,
+                        advance: advance.unwrap_or(width),
+                        rows,
+                    };
+                    glyphs.insert(c, glyph);
+                }
+            }
+            _ => {}
+        }
+    }
+    let Some((width, height, x_offset, y_offset)) = bbox else {
+        bail!("font bounding box is missing");
+    };
+    Ok(BitmapFont {
+        width,
+        height,
+        x_offset,
+        y_offset,
+        glyphs,
+    })
+}
+
+/// Parse `width height x_offset y_offset` of `FONTBOUNDINGBOX` and `BBX`.
+fn parse_bbox(args: &str) -> Result<(u32, u32, i32, i32)> {
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let [width, height, x_offset, y_offset] = parts[..] else {
+        bail!("invalid bounding box: {args}");
+    };
+    Ok((
+        width.parse().context("parse bounding box width")?,
+        height.parse().context("parse bounding box height")?,
+        x_offset.parse().context("parse bounding box x offset")?,
+        y_offset.parse().context("parse bounding box y offset")?,
+    ))
+}
+
+fn parse_hex(row: &str) -> Result<Vec<u8>> {
+    if !row.len().is_multiple_of(2) {
+        bail!("odd number of digits in bitmap row: {row}");
+    }
+    (0..row.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&row[i..i + 2], 16).context("parse bitmap row"))
+        .collect()
+}
+
+/// Fonts for encodings not covered by embedded-graphics, imported from BDF files.
+pub(crate) fn get_fonts(dir: &Path) -> Result<Vec<(&'static str, Provenance, Fff)>> {
+    let mut result = Vec::new();
+    for (file_name, provenance) in FILES {
+        let path = dir.join(format!("{file_name}.bdf"));
+        let bdf = read(&path).with_context(|| format!("load {file_name}.bdf"))?;
+        for encoding in ENCODINGS {
+            let index = encoding_index(encoding).unwrap() as u8;
+            if bdf.missing(index).is_empty() {
+                result.push((*encoding, provenance.clone(), bdf.to_fff(index)));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Load all [`EG_FILES`].
+pub(crate) fn eg_files(dir: &Path) -> Result<Vec<EgFile>> {
+    let mut result = Vec::new();
+    for (family, file_name, version) in EG_FILES {
+        let path = dir.join(format!("{file_name}.bdf"));
+        let bdf = read(&path).with_context(|| format!("load {file_name}.bdf"))?;
+        let conversion = format!("glyphs missing in embedded-graphics from bdf/{file_name}.bdf");
+        let provenance = Provenance {
+            conversion: conversion.into(),
+            ..misc(version, "")
+        };
+        result.push(EgFile {
+            family,
+            data: Arc::new(bdf.to_unicode_fff()),
+            provenance,
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+STARTFONT 2.1
+FONTBOUNDINGBOX 4 6 0 -1
+CHARS 2
+STARTCHAR A
+ENCODING 65
+BBX 3 4 0 0
+BITMAP
+40
+A0
+E0
+A0
+ENDCHAR
+STARTCHAR unmapped
+ENCODING -1
+BBX 4 6 0 -1
+BITMAP
+F0
+F0
+F0
+F0
+F0
+F0
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn test_parse() {
+        let bdf = parse(SOURCE).unwrap();
+        assert_eq!((bdf.width, bdf.height, bdf.baseline()), (4, 6, 4));
+        assert!(bdf.contains('A'));
+        assert_eq!(bdf.glyphs.len(), 1);
+        let mut rendered = String::new();
+        for y in 0..6 {
+            for x in 0..4 {
+                rendered.push(if bdf.pixel('A', x, y) { '#' } else { '.' });
+            }
+            rendered.push('\n');
+        }
+        assert_eq!(rendered, "....\n.#..\n#.#.\n###.\n#.#.\n....\n");
+    }
+}
@@ -0,0 +1,171 @@
+use crate::fff::{Fff, UNICODE};
+use crate::generator::{encoding_index, glyph_mapping};
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+/// A bitmap font loaded from any of the supported source formats.
+///
+/// Glyphs can have different sizes and advances, like in BDF.
+/// Converting into FFF lays them out into fixed cells aligned by the baseline.
+pub(crate) struct BitmapFont {
+    /// The size of the cell.
+    pub width: u32,
+    pub height: u32,
+    /// The offset of the cell from the origin (the left end of the baseline), y goes up.
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub glyphs: HashMap<char, Glyph>,
+}
+
+pub(crate) struct Glyph {
+    pub width: u32,
+    pub height: u32,
+    /// The offset of the bottom-left corner of the bitmap from the origin, y goes up.
+    pub x_offset: i32,
+    pub y_offset: i32,
+    /// How far to move the cursor after drawing the glyph.
+    pub advance: u32,
+    /// Bitmap rows, from top to bottom, each padded to a whole byte.
+    pub rows: Vec<Vec<u8>>,
+}
+
+/// Split a bitmap where rows are not padded (as in Adafruit GFX and U8g2) into rows.
+///
+/// The callback tells if the bit with the given index is set.
+pub(crate) fn pack_rows(width: u32, height: u32, bit: impl Fn(usize) -> bool) -> Vec<Vec<u8>> {
+    let mut rows = Vec::new();
+    for y in 0..height {
+        let mut row = vec![0u8; width.div_ceil(8) as usize];
+        for x in 0..width {
+            if bit((y * width + x) as usize) {
+                row[x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+impl BitmapFont {
+    /// Make a font with the cell fitting all the glyphs.
+    ///
+    /// The cell is as wide as the widest glyph or the largest advance,
+    /// and as tall as needed to fit the highest ascent and the deepest descent.
+    pub fn new(glyphs: HashMap<char, Glyph>) -> Self {
+        let width = glyphs.values().map(|g| g.width.max(g.advance)).max();
+        // Empty glyphs (like the space) have meaningless offsets.
+        let drawn = || glyphs.values().filter(|g| g.height > 0);
+        let top = drawn().map(|g| g.y_offset + g.height as i32).max();
+        let bottom = drawn().map(|g| g.y_offset).min();
+        let (top, bottom) = (top.unwrap_or(0), bottom.unwrap_or(0));
+        Self {
+            width: width.unwrap_or(0),
+            height: (top - bottom).max(0) as u32,
+            x_offset: 0,
+            y_offset: bottom,
+            glyphs,
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    /// The row of the baseline, counting from the top of the cell.
+    pub fn baseline(&self) -> u32 {
+        (self.height as i32 + self.y_offset - 1).max(0) as u32
+    }
+
+    /// If all glyphs have the same advance equal to the cell width.
+    ///
+    /// Such fonts are converted as is. In other fonts, each glyph is centered in the cell.
+    pub fn is_fixed_cell(&self) -> bool {
+        self.glyphs.values().all(|g| g.advance == self.width)
+    }
+
+    /// Check if the pixel of the character is set.
+    ///
+    /// The coordinates are relative to the top-left corner of the cell.
+    pub fn pixel(&self, c: char, x: u32, y: u32) -> bool {
+        let Some(glyph) = self.glyphs.get(&c) else {
+            return false;
+        };
+        let top = self.height as i32 + self.y_offset - (glyph.height as i32 + glyph.y_offset);
+        let centering = (self.width as i32 - glyph.advance as i32) / 2;
+        let left = glyph.x_offset - self.x_offset + centering;
+        let (col, row) = (x as i32 - left, y as i32 - top);
+        if col < 0 || row < 0 || col >= glyph.width as i32 || row >= glyph.height as i32 {
+            return false;
+        }
+        let (col, row) = (col as usize, row as usize);
+        match glyph.rows[row].get(col / 8) {
+            Some(byte) => byte & (0x80 >> (col % 8)) != 0,
+            None => false,
+        }
+    }
+
+    /// Characters of the encoding that should be visible but have no glyph.
+    pub fn missing(&self, encoding_index: u8) -> Vec<char> {
+        let mapping = glyph_mapping(encoding_index).unwrap();
+        mapping
+            .chars()
+            .filter(|&c| is_visible(c) && !self.contains(c))
+            .collect()
+    }
+
+    /// Convert the font into FFF for the given 8-bit encoding.
+    ///
+    /// Characters without a glyph are left blank.
+    pub fn to_fff(&self, encoding_index: u8) -> Fff {
+        let chars: Vec<char> = glyph_mapping(encoding_index).unwrap().chars().collect();
+        self.layout(encoding_index, &chars)
+    }
+
+    /// Convert the font into a Unicode-indexed FFF with all its glyphs.
+    pub fn to_unicode_fff(&self) -> Fff {
+        let mut chars: Vec<char> = self
+            .glyphs
+            .keys()
+            .copied()
+            .filter(|&c| is_visible(c))
+            .collect();
+        chars.sort();
+        let mut font = self.layout(UNICODE, &chars);
+        font.code_points = chars;
+        font
+    }
+
+    /// Convert into FFF in the given encoding or, for `unicode`, a Unicode-indexed FFF.
+    pub fn convert(&self, encoding: &str) -> Result<Fff> {
+        if encoding == "unicode" {
+            return Ok(self.to_unicode_fff());
+        }
+        let Some(index) = encoding_index(encoding) else {
+            bail!("unknown encoding: {encoding}");
+        };
+        Ok(self.to_fff(index as u8))
+    }
+
+    fn layout(&self, encoding: u8, chars: &[char]) -> Fff {
+        let template = Fff {
+            encoding,
+            width: self.width,
+            height: self.height,
+            baseline: self.baseline(),
+            atlas_width: 0,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        Fff::from_glyphs(&template, chars.len(), |g, x, y| self.pixel(chars[g], x, y))
+    }
+}
+
+/// If the character has a visible glyph.
+///
+/// Control characters, placeholders for undefined code points,
+/// and invisible direction marks don't need a glyph.
+pub(crate) fn is_visible(c: char) -> bool {
+    !c.is_control() && !matches!(c, '\u{fffd}' | '\u{200e}' | '\u{200f}')
+}
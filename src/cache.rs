@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Where caches are stored, outside of the output directories,
+/// so that they are never deployed or packaged.
+const CACHE_DIR: &str = "target/build-cache";
+
+/// The name of the cache file that older versions kept in the output directory.
+const LEGACY_FILE_NAME: &str = ".build-cache";
+
+/// Hashes of inputs for all generated files, for skipping unchanged outputs.
+///
+/// The cache is stored in [`CACHE_DIR`] as a text file named after the output
+/// directory, one `hash path` pair per line. The hashes are not stable across
+/// Rust versions, so updating the compiler may cause a full rebuild.
+///
+/// A full build also stores the hash of all its inputs on an `inputs hash` line,
+/// so that the next build can be skipped if nothing changed.
+pub(crate) struct BuildCache {
+    root: PathBuf,
+    path: PathBuf,
+    inputs: Option<u64>,
+    old: HashMap<String, u64>,
+    new: Mutex<HashMap<String, u64>>,
+}
+
+impl BuildCache {
+    pub fn load(root: &Path) -> Self {
+        let path = cache_path(root);
+        let mut inputs = None;
+        let mut old = HashMap::new();
+        if let Ok(raw) = std::fs::read_to_string(&path) {
+            for line in raw.lines() {
+                if let Some(hash) = line.strip_prefix("inputs ") {
+                    inputs = u64::from_str_radix(hash, 16).ok();
+                    continue;
+                }
+                let Some((hash, path)) = line.split_once(' ') else {
+                    continue;
+                };
+                if let Ok(hash) = u64::from_str_radix(hash, 16) {
+                    old.insert(path.to_string(), hash);
+                }
+            }
+        }
+        Self {
+            root: root.to_path_buf(),
+            path,
+            inputs,
+            old,
+            new: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check if the file was generated from the same inputs and still exists.
+    ///
+    /// Either way, the input hash is remembered for the next build.
+    pub fn is_fresh(&self, path: &Path, hash: u64) -> bool {
+        let key = self.key(path);
+        let fresh = self.old.get(&key) == Some(&hash) && path.exists();
+        self.new.lock().unwrap().insert(key, hash);
+        fresh
+    }
+
+    /// Check if the last full build had the same inputs and all its files still exist.
+    pub fn is_unchanged(&self, inputs: u64) -> bool {
+        self.inputs == Some(inputs) && self.old.keys().all(|path| self.root.join(path).exists())
+    }
+
+    /// Save the cache and remove files that are not generated anymore.
+    ///
+    /// The hash of inputs is given only by full builds.
+    pub fn save(self, inputs: Option<u64>) -> Result<()> {
+        let new = self.new.into_inner().unwrap();
+        for path in self.old.keys() {
+            if !new.contains_key(path) {
+                let path = self.root.join(path);
+                if path.exists() {
+                    std::fs::remove_file(path).context("remove stale file")?;
+                }
+            }
+        }
+        let mut lines: Vec<String> = new
+            .iter()
+            .map(|(path, hash)| format!("{hash:016x} {path}"))
+            .collect();
+        lines.sort_by(|a, b| a[17..].cmp(&b[17..]));
+        if let Some(inputs) = inputs {
+            lines.insert(0, format!("inputs {inputs:016x}"));
+        }
+        lines.push(String::new());
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).context("create build cache dir")?;
+        }
+        std::fs::write(&self.path, lines.join("\n")).context("write build cache")?;
+        let legacy = self.root.join(LEGACY_FILE_NAME);
+        if legacy.exists() {
+            std::fs::remove_file(legacy).context("remove old build cache")?;
+        }
+        Ok(())
+    }
+
+    /// Save the cache after a build that generated only some of the files.
+    ///
+    /// Unlike [`BuildCache::save`], files that weren't checked in this build are kept.
+    pub fn save_partial(self) -> Result<()> {
+        {
+            let mut new = self.new.lock().unwrap();
+            for (path, hash) in &self.old {
+                new.entry(path.clone()).or_insert(*hash);
+            }
+        }
+        self.save(None)
+    }
+
+    fn key(&self, path: &Path) -> String {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        path.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// The cache file for the output directory, like `target/build-cache/root_crate_public`.
+fn cache_path(root: &Path) -> PathBuf {
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    let name: String = root
+        .to_string_lossy()
+        .trim_start_matches(['/', '\\'])
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Path::new(CACHE_DIR).join(name)
+}
+
+pub(crate) fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
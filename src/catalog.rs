@@ -0,0 +1,329 @@
+use crate::bdf::{self, MISC_LICENSE};
+use crate::blocks::{coverage, Coverage};
+use crate::checksums::sha256_hex;
+use crate::config::Config;
+use crate::cp437;
+use crate::extra_fonts::{get_fonts, load_atlases, License, Provenance, EG_LICENSE, EG_PROVENANCE};
+use crate::fff::Fff;
+use crate::fonts::FONTS;
+use crate::generator::{encoding_index, encoding_name};
+use crate::import::load;
+use crate::manifest::Manifest;
+use crate::merge::{get_merges, Converted};
+use crate::transform::Transform;
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+use serde::{Serialize, Serializer};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A font published in the catalog.
+#[derive(Serialize)]
+pub(crate) struct Font {
+    pub family: String,
+    pub width: u32,
+    pub height: u32,
+    pub encoding: &'static str,
+    /// Licenses of all fonts the glyphs come from.
+    pub licenses: Vec<License>,
+    /// Where the glyphs come from, one entry per source font.
+    pub provenance: Vec<Provenance>,
+    /// The family of the font this one is derived from.
+    pub derived_from: Option<String>,
+    /// How the font is derived, serialized as its label.
+    #[serde(serialize_with = "serialize_transform")]
+    pub transform: Option<Transform>,
+    /// If glyphs are rotated, for vertical text.
+    pub rotated: bool,
+    /// Hex-encoded SHA-256 of the font file.
+    pub sha256: String,
+    /// Glyphs per Unicode block, only for Unicode-indexed fonts.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<Coverage>,
+    pub style: Style,
+    /// Visible characters of the encoding that have a non-empty glyph, in percent.
+    pub coverage: u32,
+    #[serde(skip)]
+    pub data: Fff,
+}
+
+fn serialize_transform<S: Serializer>(
+    transform: &Option<Transform>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    transform.map(Transform::label).serialize(serializer)
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Style {
+    Regular,
+    Bold,
+    Italic,
+}
+
+impl Style {
+    /// The style of the font family, like `eg-bold` or `profont-bold`.
+    ///
+    /// Families of derived fonts have the transform as a suffix, so that bold
+    /// variants are bold too. `ibm437b` is bold, `ibm437r` is regular.
+    fn of(family: &str) -> Self {
+        if family.ends_with("-bold") || family == "ibm437b" {
+            Self::Bold
+        } else if family.ends_with("-italic") {
+            Self::Italic
+        } else {
+            Self::Regular
+        }
+    }
+}
+
+impl Font {
+    fn new(
+        family: &str,
+        encoding: &'static str,
+        licenses: Vec<License>,
+        provenance: Vec<Provenance>,
+        data: Fff,
+    ) -> Result<Self> {
+        let mut font = Self {
+            family: family.to_string(),
+            width: data.width,
+            height: data.height,
+            encoding,
+            licenses,
+            provenance,
+            derived_from: None,
+            transform: None,
+            rotated: false,
+            sha256: String::new(),
+            blocks: coverage(&data.code_points),
+            style: Style::of(family),
+            coverage: glyph_coverage(&data),
+            data,
+        };
+        font.update_metadata()?;
+        Ok(font)
+    }
+
+    /// Put the family, licenses, and provenance into the font file metadata.
+    ///
+    /// The metadata is a part of the file, so the checksum is updated too.
+    fn update_metadata(&mut self) -> Result<()> {
+        let mut metadata = vec![("family", self.family.clone())];
+        for license in &self.licenses {
+            metadata.push(("license", license.spdx.to_string()));
+            if let Some(copyright) = &license.copyright {
+                metadata.push(("copyright", copyright.to_string()));
+            }
+        }
+        for source in &self.provenance {
+            metadata.push(("author", source.author.to_string()));
+            metadata.push(("source", source.url.to_string()));
+            if let Some(version) = &source.version {
+                metadata.push(("version", version.to_string()));
+            }
+            metadata.push(("conversion", source.conversion.to_string()));
+        }
+        if let (Some(from), Some(transform)) = (&self.derived_from, &self.transform) {
+            metadata.push(("derived_from", from.clone()));
+            metadata.push(("transform", transform.label()));
+        }
+        self.data.metadata = metadata
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        let raw = self
+            .data
+            .to_bytes()
+            .with_context(|| format!("serialize {}", self.name()))?;
+        self.sha256 = sha256_hex(&raw);
+        Ok(())
+    }
+
+    /// The file name without extension, like `eg_4x6`.
+    pub fn name(&self) -> String {
+        format!("{}_{}x{}", self.family, self.width, self.height)
+    }
+}
+
+/// How many visible characters of the font have a non-empty glyph, in percent.
+///
+/// Whitespace has no pixels and is skipped.
+fn glyph_coverage(font: &Fff) -> u32 {
+    let glyphs: Vec<usize> = font
+        .visible_glyphs()
+        .into_iter()
+        .filter(|(c, _)| !c.is_whitespace())
+        .map(|(_, glyph)| glyph)
+        .collect();
+    if glyphs.is_empty() {
+        return 0;
+    }
+    let drawn = glyphs
+        .iter()
+        .filter(|&&glyph| {
+            (0..font.height).any(|y| (0..font.width).any(|x| font.pixel(glyph, x, y)))
+        })
+        .count();
+    (drawn * 100 / glyphs.len()) as u32
+}
+
+/// Fonts that have distinct output files.
+///
+/// Fonts can share the file name if an imported font has the same family and size
+/// as another one. For such fonts, only the last one is kept, same as if the files
+/// were written one after another.
+pub(crate) fn unique_files(fonts: &[Font]) -> Vec<&Font> {
+    let mut seen = HashSet::new();
+    let mut result: Vec<&Font> = fonts
+        .iter()
+        .rev()
+        .filter(|f| seen.insert((f.encoding, f.name())))
+        .collect();
+    result.reverse();
+    result
+}
+
+/// Collect all fonts of the catalog, including merged and derived ones.
+///
+/// Atlases are loaded and decoded only once, and all generated files
+/// (fonts, images, HTML pages) are produced from the result.
+/// Fonts excluded by the config are skipped, and so are variants derived from them.
+pub(crate) fn build_catalog(manifest: &Manifest, config: &Config) -> Result<Vec<Font>> {
+    let atlases = load_atlases(&config.atlas).context("load atlases")?;
+    let extra_fonts = get_fonts(&atlases);
+    let converted = Converted::default();
+    let mut result = Vec::new();
+    let eg_files = bdf::eg_files(&config.bdf).context("load eg BDF files")?;
+    let merges: Vec<_> = get_merges(&extra_fonts, &eg_files)
+        .into_iter()
+        .filter(|m| config.includes(m.family, m.encoding))
+        .collect();
+    let merged = merges
+        .par_iter()
+        .map(|merge| -> Result<Font> {
+            let data = merge.merge(&converted).context("merge font")?;
+            Font::new(
+                merge.family,
+                merge.encoding,
+                merge.licenses(),
+                merge.provenance(),
+                data,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    result.extend(merged);
+    let cp437_fonts = cp437::get_fonts(&config.raw_atlas, &extra_fonts, &eg_files)?;
+    for font in cp437_fonts {
+        if config.includes(font.family, "cp437") {
+            let font = Font::new(
+                font.family,
+                "cp437",
+                font.licenses,
+                font.provenance,
+                font.data,
+            )?;
+            result.push(font);
+        }
+    }
+    for (encoding, provenance, data) in bdf::get_fonts(&config.bdf).context("import BDF fonts")? {
+        if config.includes("misc", encoding) {
+            let font = Font::new(
+                "misc",
+                encoding,
+                vec![MISC_LICENSE.clone()],
+                vec![provenance],
+                data,
+            )?;
+            result.push(font);
+        }
+    }
+    let manifest_dir = config.manifest.parent().unwrap_or(Path::new("."));
+    for import in &manifest.imports {
+        let encoding = match encoding_index(&import.encoding) {
+            Some(index) => encoding_name(index).unwrap(),
+            None if import.encoding == "unicode" => "unicode",
+            None => bail!("unknown encoding of imported font: {}", import.encoding),
+        };
+        if !config.includes(&import.family, encoding) {
+            continue;
+        }
+        let path = manifest_dir.join(&import.path);
+        let source = load(&path).with_context(|| format!("import {}", path.display()))?;
+        let data = source.convert(encoding)?;
+        result.push(Font::new(
+            &import.family,
+            encoding,
+            vec![import.license()],
+            vec![import.provenance()],
+            data,
+        )?);
+    }
+    for font in &extra_fonts {
+        if !config.includes(font.family, "ascii") {
+            continue;
+        }
+        let data = Fff::clone(&converted.get(0, &font.font));
+        let provenance = vec![font.provenance.clone()];
+        result.push(Font::new(
+            font.family,
+            "ascii",
+            vec![font.license.clone()],
+            provenance,
+            data,
+        )?);
+    }
+
+    let mut eg_fonts = Vec::new();
+    for (encoding, fonts) in FONTS {
+        let index = encoding_index(encoding).unwrap();
+        for (family, font) in fonts.iter() {
+            if config.includes(family, encoding) {
+                eg_fonts.push((family, encoding, index, font));
+            }
+        }
+    }
+    let eg_fonts = eg_fonts
+        .into_par_iter()
+        .map(|(family, encoding, index, font)| -> Result<Font> {
+            let data = Fff::clone(&converted.get(index, font));
+            Font::new(
+                family,
+                encoding,
+                vec![EG_LICENSE.clone()],
+                vec![EG_PROVENANCE.clone()],
+                data,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    result.extend(eg_fonts);
+
+    let mut sources = Vec::new();
+    for variant in &manifest.variants {
+        for font in &result {
+            if variant.matches(&font.family, font.encoding, font.width, font.height) {
+                sources.push((variant, font));
+            }
+        }
+    }
+    let derived: Vec<Font> = sources
+        .into_par_iter()
+        .map(|(variant, font)| -> Result<Font> {
+            let data = variant.transform.apply(&font.data).with_context(|| {
+                format!("derive {} from {}", variant.family_name(), font.name())
+            })?;
+            let family = variant.family_name();
+            let licenses = font.licenses.clone();
+            let provenance = font.provenance.clone();
+            let mut derived = Font::new(&family, font.encoding, licenses, provenance, data)?;
+            derived.derived_from = Some(font.family.clone());
+            derived.transform = Some(variant.transform);
+            derived.rotated = variant.transform.is_rotation();
+            derived.update_metadata()?;
+            Ok(derived)
+        })
+        .collect::<Result<_>>()?;
+    result.extend(derived);
+    Ok(result)
+}
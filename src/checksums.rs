@@ -0,0 +1,67 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font};
+use crate::image::sample_path;
+use crate::layout::sample;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const FILE_NAME: &str = "SHA256SUMS";
+
+/// Hex-encoded SHA-256 of the data.
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    let digest = Sha256::digest(data);
+    let mut result = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        result.push_str(&format!("{byte:02x}"));
+    }
+    result
+}
+
+/// Write `SHA256SUMS` for all generated fonts and images.
+///
+/// The format is the same as produced by `sha256sum`, so the downloaded files
+/// can be checked with `sha256sum --check --ignore-missing SHA256SUMS`.
+/// Hashes are computed from the files on disk, so it must run after they are generated.
+///
+/// Returns how many files were updated (0 or 1).
+pub(crate) fn write_checksums(root: &Path, fonts: &[Font], cache: &BuildCache) -> Result<usize> {
+    let mut paths = Vec::new();
+    for font in unique_files(fonts) {
+        let name = font.name();
+        paths.push(format!("fonts/{}/{name}.fff", font.encoding));
+        paths.push(format!("images/{}/{name}.png", font.encoding));
+        if sample(font.encoding).is_some() {
+            paths.push(format!("images/{}", sample_path(font)));
+        }
+    }
+    paths.sort();
+    let lines: Vec<String> = paths
+        .par_iter()
+        .map(|path| -> Result<String> {
+            let raw = std::fs::read(root.join(path)).context("read generated file")?;
+            Ok(format!("{}  {path}\n", sha256_hex(&raw)))
+        })
+        .collect::<Result<_>>()?;
+    let content = lines.concat();
+
+    let path = root.join(FILE_NAME);
+    if cache.is_fresh(&path, hash_of(&content)) {
+        return Ok(0);
+    }
+    std::fs::write(&path, content).context("write checksums")?;
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        let hash = sha256_hex(b"abc");
+        let expected = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(hash, expected);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{build_catalog, unique_files, Font};
+use crate::config::{load_config, BuildOptions};
+use crate::generator::save_all_fonts;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// The name of the generated crate.
+const CRATE_NAME: &str = "firefly-fonts-data";
+
+#[derive(clap::Args)]
+pub(crate) struct CodegenArgs {
+    /// Where to generate the crate.
+    #[arg(long, default_value = CRATE_NAME)]
+    dir: PathBuf,
+
+    /// Fonts to include (`--families` and `--encodings`) and where their sources are.
+    #[command(flatten)]
+    build: BuildOptions,
+}
+
+/// Generate a Rust crate that embeds catalog fonts, a feature per family.
+pub(crate) fn cmd_codegen(args: &CodegenArgs) -> Result<()> {
+    let (config, manifest) = load_config(&args.build).context("load config")?;
+    let fonts = build_catalog(&manifest, &config).context("build catalog")?;
+    generate(&args.dir, &fonts)
+}
+
+/// Write the crate with the given fonts into the directory.
+///
+/// Like the site, the crate is built incrementally, and files of fonts
+/// and families that aren't included anymore are removed. The build cache
+/// is kept outside of the crate, so it's never published.
+fn generate(dir: &Path, fonts: &[Font]) -> Result<()> {
+    let unique = unique_files(fonts);
+    let mut families: BTreeMap<&str, Vec<&Font>> = BTreeMap::new();
+    for font in &unique {
+        families.entry(&font.family).or_default().push(font);
+    }
+
+    // Font files are the same as on the site, included with `include_bytes!`.
+    let fonts_path = dir.join("fonts");
+    std::fs::create_dir_all(&fonts_path).context("create fonts dir")?;
+    let cache = BuildCache::load(dir);
+    save_all_fonts(&fonts_path, fonts, &cache).context("generate fonts")?;
+
+    let src_path = dir.join("src");
+    std::fs::create_dir_all(&src_path).context("create src dir")?;
+    write(&dir.join("Cargo.toml"), &cargo_toml(&families), &cache)?;
+    write(&src_path.join("lib.rs"), &lib_rs(&families), &cache)?;
+    for (family, fonts) in &families {
+        let path = src_path.join(format!("{}.rs", module_name(family)));
+        write(&path, &family_rs(family, fonts), &cache)?;
+    }
+    cache.save(None).context("save build cache")?;
+    println!(
+        "Generated {CRATE_NAME} with {} fonts in {} families",
+        unique.len(),
+        families.len(),
+    );
+    Ok(())
+}
+
+/// Write the file if its content has changed, so cargo doesn't rebuild the crate.
+fn write(path: &Path, content: &str, cache: &BuildCache) -> Result<()> {
+    if cache.is_fresh(path, hash_of(content)) {
+        return Ok(());
+    }
+    std::fs::write(path, content).with_context(|| format!("write {}", path.display()))
+}
+
+/// Family names have dashes (`pico8-outline`), modules can't.
+fn module_name(family: &str) -> String {
+    family.replace('-', "_")
+}
+
+fn cargo_toml(families: &BTreeMap<&str, Vec<&Font>>) -> String {
+    let mut out = String::new();
+    writeln!(out, "[package]").unwrap();
+    writeln!(out, "name = \"{CRATE_NAME}\"").unwrap();
+    writeln!(out, "version = \"{}\"", env!("CARGO_PKG_VERSION")).unwrap();
+    writeln!(out, "edition = \"2021\"").unwrap();
+    writeln!(
+        out,
+        "description = \"Bitmap fonts for Firefly Zero in the Firefly Font Format\"",
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "[features]").unwrap();
+    writeln!(out, "default = []").unwrap();
+    let all: Vec<String> = families.keys().map(|f| format!("\"{f}\"")).collect();
+    writeln!(out, "all = [{}]", all.join(", ")).unwrap();
+    for family in families.keys() {
+        writeln!(out, "{family} = []").unwrap();
+    }
+    out
+}
+
+fn lib_rs(families: &BTreeMap<&str, Vec<&Font>>) -> String {
+    let mut out = String::new();
+    out.push_str(concat!(
+        "//! Bitmap fonts for Firefly Zero in the Firefly Font Format (FFF).\n",
+        "//!\n",
+        "//! Each font family is a module behind a feature with the family name\n",
+        "//! (modules have underscores instead of dashes), and `all` enables all of them.\n",
+        "//! Generated by `firefly-fonts codegen`, don't edit.\n",
+        "#![no_std]\n",
+        "\n",
+        "/// A font file and its metadata.\n",
+        "pub struct Font {\n",
+        "    pub family: &'static str,\n",
+        "    /// The encoding, like `ascii` or `koi8_r`, or `unicode` for Unicode-indexed fonts.\n",
+        "    pub encoding: &'static str,\n",
+        "    /// The glyph width in pixels.\n",
+        "    pub width: u8,\n",
+        "    /// The glyph height in pixels.\n",
+        "    pub height: u8,\n",
+        "    /// The row of the baseline, counting from the top of the glyph.\n",
+        "    pub baseline: u8,\n",
+        "    /// SPDX identifiers of the font licenses.\n",
+        "    pub licenses: &'static [&'static str],\n",
+        "    /// The font file (FFF).\n",
+        "    pub data: &'static [u8],\n",
+        "}\n",
+    ));
+    for family in families.keys() {
+        writeln!(out).unwrap();
+        writeln!(out, "#[cfg(feature = \"{family}\")]").unwrap();
+        writeln!(out, "pub mod {};", module_name(family)).unwrap();
+    }
+    out
+}
+
+fn family_rs(family: &str, fonts: &[&Font]) -> String {
+    let mut out = String::new();
+    writeln!(out, "//! The `{family}` font family.").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "use crate::Font;").unwrap();
+    for font in fonts {
+        let licenses: Vec<String> = font
+            .licenses
+            .iter()
+            .map(|l| format!("\"{}\"", l.spdx))
+            .collect();
+        writeln!(out).unwrap();
+        writeln!(out, "/// {} {}x{}.", font.encoding, font.width, font.height).unwrap();
+        writeln!(
+            out,
+            "pub const {}_{}X{}: Font = Font {{",
+            font.encoding.to_uppercase(),
+            font.width,
+            font.height,
+        )
+        .unwrap();
+        writeln!(out, "    family: \"{family}\",").unwrap();
+        writeln!(out, "    encoding: \"{}\",", font.encoding).unwrap();
+        writeln!(out, "    width: {},", font.width).unwrap();
+        writeln!(out, "    height: {},", font.height).unwrap();
+        writeln!(out, "    baseline: {},", font.data.baseline).unwrap();
+        writeln!(out, "    licenses: &[{}],", licenses.join(", ")).unwrap();
+        writeln!(
+            out,
+            "    data: include_bytes!(\"../fonts/{}/{}.fff\"),",
+            font.encoding,
+            font.name(),
+        )
+        .unwrap();
+        writeln!(out, "}};").unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let (mut config, manifest) = load_config(&BuildOptions::default()).unwrap();
+        // Variants like `pico8-outline` are included with their source family.
+        config.families = vec!["pico8".to_string()];
+        let fonts = build_catalog(&manifest, &config).unwrap();
+        let dir = std::env::temp_dir().join("test_codegen");
+        _ = std::fs::remove_dir_all(&dir);
+        generate(&dir, &fonts).unwrap();
+
+        let cargo_toml = std::fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains("name = \"firefly-fonts-data\""));
+        assert!(cargo_toml.contains("all = [\"pico8\", \"pico8-ccw\","));
+        assert!(cargo_toml.contains("\npico8-outline = []\n"));
+        let lib_rs = std::fs::read_to_string(dir.join("src/lib.rs")).unwrap();
+        assert!(lib_rs.contains("#![no_std]"));
+        assert!(lib_rs.contains("#[cfg(feature = \"pico8-outline\")]\npub mod pico8_outline;"));
+        let family_rs = std::fs::read_to_string(dir.join("src/pico8.rs")).unwrap();
+        assert!(family_rs.contains("pub const ASCII_4X6: Font"));
+        assert!(family_rs.contains("include_bytes!(\"../fonts/ascii/pico8_4x6.fff\")"));
+        // The build cache isn't a part of the crate.
+        assert!(!dir.join(".build-cache").exists());
+
+        // Modules and fonts of families that aren't generated anymore are removed.
+        let pico8: Vec<Font> = fonts.into_iter().filter(|f| f.family == "pico8").collect();
+        generate(&dir, &pico8).unwrap();
+        assert!(dir.join("src/pico8.rs").exists());
+        assert!(!dir.join("src/pico8_outline.rs").exists());
+        let lib_rs = std::fs::read_to_string(dir.join("src/lib.rs")).unwrap();
+        assert!(!lib_rs.contains("pico8_outline"));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font, Style};
+use crate::device::{scaled_path, wrap_words, SCALE, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::extra_fonts::License;
+use crate::fff::Fff;
+use crate::image::{write_indexed, write_png};
+use crate::transform::Transform;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The version of the screen layout in `render_screen`.
+///
+/// It's a part of the cache key, so bump it when changing the layout.
+const LAYOUT_VERSION: u32 = 1;
+
+/// The gap between the screen edges and the text.
+const PADDING: u32 = 4;
+
+/// Colors of the enlarged screens: black text on white, same as the 1x ones.
+static PALETTE: [[u8; 3]; 16] = {
+    let mut palette = [[0; 3]; 16];
+    palette[0] = [0xff; 3];
+    palette
+};
+
+/// The text that all fonts render on the simulated screen, wrapped by words.
+const TEXT: &str = "The quick brown fox jumps over the lazy dog. \
+    THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG! 0123456789 \
+    (a+b)*c=d, [x]{y}<z> #$%&@ \"quotes\" 'apostrophes' ~_^|\\/;:?";
+
+/// A pre-generated comparison page with a group of fonts.
+pub(crate) struct Group {
+    pub slug: &'static str,
+    pub title: &'static str,
+    includes: fn(&Font) -> bool,
+}
+
+pub(crate) static GROUPS: &[Group] = &[
+    Group {
+        slug: "all",
+        title: "All fonts",
+        includes: |_| true,
+    },
+    Group {
+        slug: "small",
+        title: "Up to 6px tall",
+        includes: |f| f.height <= 6,
+    },
+    Group {
+        slug: "medium",
+        title: "7 to 10px tall",
+        includes: |f| (7..=10).contains(&f.height),
+    },
+    Group {
+        slug: "large",
+        title: "Over 10px tall",
+        includes: |f| f.height > 10,
+    },
+    Group {
+        slug: "bold",
+        title: "Bold",
+        includes: |f| f.style == Style::Bold,
+    },
+    Group {
+        slug: "decorative",
+        title: "Outlined, shadowed, and inverted",
+        includes: |f| {
+            use Transform::{Invert, Outline, Shadow};
+            matches!(f.transform, Some(Outline | Shadow | Invert))
+        },
+    },
+];
+
+/// A font on a comparison page, with its metrics.
+#[derive(Serialize)]
+pub(crate) struct Entry<'a> {
+    pub name: String,
+    pub family: &'a str,
+    pub width: u32,
+    pub height: u32,
+    pub baseline: u32,
+    pub glyphs: usize,
+    /// How many characters fit into a line of the screen.
+    pub columns: u32,
+    /// How many lines fit on the screen.
+    pub lines: u32,
+    pub licenses: &'a [License],
+}
+
+/// Fonts that can be compared: ASCII fonts, since the sample text is ASCII,
+/// without rotated ones, since they are for vertical text.
+pub(crate) fn comparable(fonts: &[Font]) -> Vec<&Font> {
+    let mut fonts: Vec<&Font> = unique_files(fonts)
+        .into_iter()
+        .filter(|f| f.encoding == "ascii" && !f.rotated)
+        .collect();
+    fonts.sort_by(|a, b| (a.height, a.width, &a.family).cmp(&(b.height, b.width, &b.family)));
+    fonts
+}
+
+impl Group {
+    /// Fonts of the group with their metrics, from the smallest to the largest.
+    pub fn entries<'a>(&self, fonts: &[&'a Font]) -> Vec<Entry<'a>> {
+        fonts
+            .iter()
+            .filter(|f| (self.includes)(f))
+            .map(|f| Entry {
+                name: f.name(),
+                family: &f.family,
+                width: f.width,
+                height: f.height,
+                baseline: f.data.baseline,
+                glyphs: f.data.glyph_count(),
+                columns: (SCREEN_WIDTH - 2 * PADDING) / f.width,
+                lines: (SCREEN_HEIGHT - 2 * PADDING) / (f.height + 1),
+                licenses: &f.licenses,
+            })
+            .collect()
+    }
+}
+
+/// Render the sample text on a simulated screen for all comparable fonts,
+/// at 1x and enlarged by [`SCALE`].
+///
+/// Returns how many images were updated.
+pub(crate) fn screens_to_images(
+    fonts: &[Font],
+    out_dir: &Path,
+    cache: &BuildCache,
+) -> Result<usize> {
+    fs::create_dir_all(out_dir).context("create screens dir")?;
+    let updated = AtomicUsize::new(0);
+    comparable(fonts)
+        .par_iter()
+        .try_for_each(|font| -> Result<()> {
+            let out_path = out_dir.join(format!("{}.png", font.name()));
+            let hash = hash_of(&(font.data.to_bytes()?, LAYOUT_VERSION, PADDING, TEXT));
+            let scaled = scaled_path(&out_path, SCALE);
+            // Both images are checked, so that neither goes missing or stale.
+            let fresh = cache.is_fresh(&out_path, hash) & cache.is_fresh(&scaled, hash);
+            if !fresh {
+                let data = render_screen(&font.data);
+                write_png(&data, SCREEN_WIDTH, SCREEN_HEIGHT, &out_path)
+                    .context("render screen")?;
+                write_scaled(&data, &scaled).context("render scaled screen")?;
+                updated.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        })?;
+    Ok(updated.into_inner())
+}
+
+/// Write 1-bit packed screen pixels as a PNG enlarged by [`SCALE`].
+fn write_scaled(data: &[u8], out_path: &Path) -> Result<()> {
+    let pixels: Vec<u8> = (0..data.len() * 8)
+        .map(|bit| (data[bit / 8] >> (7 - bit % 8)) & 1)
+        .collect();
+    write_indexed(
+        &pixels,
+        SCREEN_WIDTH,
+        SCREEN_HEIGHT,
+        &PALETTE,
+        SCALE,
+        out_path,
+    )
+}
+
+/// Draw the sample text, wrapped by words, with 1px between lines.
+/// Lines that don't fit on the screen are cut off.
+fn render_screen(font: &Fff) -> Vec<u8> {
+    let columns = ((SCREEN_WIDTH - 2 * PADDING) / font.width).max(1) as usize;
+    let lines = wrap_words(TEXT, columns);
+
+    let mut data = vec![0u8; (SCREEN_WIDTH * SCREEN_HEIGHT / 8) as usize];
+    for (row, line) in lines.iter().enumerate() {
+        let top = PADDING + row as u32 * (font.height + 1);
+        for (column, c) in line.chars().enumerate() {
+            let Some(glyph) = font.glyph_index(c) else {
+                continue;
+            };
+            let left = PADDING + column as u32 * font.width;
+            for y in 0..font.height {
+                for x in 0..font.width {
+                    let (sx, sy) = (left + x, top + y);
+                    if sx < SCREEN_WIDTH && sy < SCREEN_HEIGHT && font.pixel(glyph, x, y) {
+                        let bit = (sy * SCREEN_WIDTH + sx) as usize;
+                        data[bit / 8] |= 0x80 >> (bit % 8);
+                    }
+                }
+            }
+        }
+    }
+    data
+}
@@ -0,0 +1,197 @@
+use crate::manifest::{load_manifest, Manifest};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_BASE_URL: &str = "https://fonts.fireflyzero.com/";
+
+/// Options for building the site.
+///
+/// Each option can be set in the `[build]` section of the manifest or with a CLI flag.
+/// CLI flags take precedence. Relative paths from the CLI are resolved
+/// from the current directory, and relative paths from the manifest
+/// are resolved from the directory where the manifest is.
+#[derive(clap::Args, Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BuildOptions {
+    /// Path to the manifest [default: manifest.toml in the current directory, if any].
+    #[arg(long)]
+    #[serde(skip)]
+    pub manifest: Option<PathBuf>,
+
+    /// Directory with font atlases [default: atlas].
+    #[arg(long)]
+    pub atlas: Option<PathBuf>,
+
+    /// Directory with raw glyph sheets [default: raw_atlas].
+    #[arg(long)]
+    pub raw_atlas: Option<PathBuf>,
+
+    /// Directory with BDF fonts [default: bdf].
+    #[arg(long)]
+    pub bdf: Option<PathBuf>,
+
+    /// Directory with license texts [default: licenses].
+    #[arg(long)]
+    pub licenses: Option<PathBuf>,
+
+    /// Directory with HTML templates [default: templates].
+    #[arg(long)]
+    pub templates: Option<PathBuf>,
+
+    /// Where to put the generated site [default: public].
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// The URL where the site is published.
+    #[arg(long)]
+    pub base_url: Option<String>,
+
+    /// Include only these encodings, comma-separated.
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default)]
+    pub encodings: Vec<String>,
+
+    /// Include only these font families, comma-separated.
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default)]
+    pub families: Vec<String>,
+}
+
+/// Build options with all paths resolved.
+pub(crate) struct Config {
+    pub manifest: PathBuf,
+    pub atlas: PathBuf,
+    pub raw_atlas: PathBuf,
+    pub bdf: PathBuf,
+    pub licenses: PathBuf,
+    pub templates: PathBuf,
+    pub output: PathBuf,
+    /// The URL where the site is published, always with a trailing slash.
+    pub base_url: String,
+    /// Encodings to include. Empty if all should be included.
+    pub encodings: Vec<String>,
+    /// Font families to include. Empty if all should be included.
+    pub families: Vec<String>,
+}
+
+impl Config {
+    /// Check if the font should be included into the catalog.
+    pub fn includes(&self, family: &str, encoding: &str) -> bool {
+        let family_ok = self.families.is_empty() || self.families.iter().any(|f| f == family);
+        family_ok && self.includes_encoding(encoding)
+    }
+
+    pub fn includes_encoding(&self, encoding: &str) -> bool {
+        self.encodings.is_empty() || self.encodings.iter().any(|e| e == encoding)
+    }
+
+    /// A hash of everything the site is built from: the build tool itself,
+    /// the options, and all source files, including imported fonts.
+    ///
+    /// The tool is identified by the size and the modification time of its executable.
+    pub fn input_hash(&self, manifest: &Manifest) -> Result<u64> {
+        let mut hasher = DefaultHasher::new();
+        let exe = std::env::current_exe().context("find executable")?;
+        let meta = std::fs::metadata(exe).context("read executable metadata")?;
+        (meta.len(), meta.modified().ok()).hash(&mut hasher);
+        (&self.base_url, &self.encodings, &self.families).hash(&mut hasher);
+        let manifest_dir = self.manifest.parent().unwrap_or(Path::new("."));
+        let mut paths = vec![self.manifest.clone()];
+        paths.extend(manifest.imports.iter().map(|i| manifest_dir.join(&i.path)));
+        for dir in [
+            &self.atlas,
+            &self.raw_atlas,
+            &self.bdf,
+            &self.licenses,
+            &self.templates,
+        ] {
+            paths.extend(files_in(dir));
+        }
+        for path in paths {
+            path.hash(&mut hasher);
+            std::fs::read(&path).ok().hash(&mut hasher);
+        }
+        Ok(hasher.finish())
+    }
+}
+
+/// All files in the directory and its subdirectories, sorted.
+pub(crate) fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return result;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            result.extend(files_in(&path));
+        } else {
+            result.push(path);
+        }
+    }
+    result.sort();
+    result
+}
+
+/// Load the manifest and combine build options from it with the ones from the CLI.
+pub(crate) fn load_config(cli: &BuildOptions) -> Result<(Config, Manifest)> {
+    let cwd = std::env::current_dir().context("get current dir")?;
+    let (manifest_path, manifest) = match &cli.manifest {
+        Some(path) => {
+            let path = cwd.join(path);
+            let manifest = load_manifest(&path)
+                .with_context(|| format!("load manifest {}", path.display()))?;
+            (path, manifest)
+        }
+        // Without a manifest in the current dir, the defaults are used.
+        None => {
+            let path = cwd.join("manifest.toml");
+            let manifest = if path.exists() {
+                load_manifest(&path).context("load manifest.toml")?
+            } else {
+                Manifest::default()
+            };
+            (path, manifest)
+        }
+    };
+    let root = manifest_path.parent().unwrap_or(&cwd).to_path_buf();
+    let from_file = &manifest.build;
+
+    let resolve = |cli: &Option<PathBuf>, file: &Option<PathBuf>, default: &str| match (cli, file) {
+        (Some(path), _) => cwd.join(path),
+        (None, Some(path)) => root.join(path),
+        (None, None) => root.join(default),
+    };
+    let mut base_url = cli
+        .base_url
+        .clone()
+        .or_else(|| from_file.base_url.clone())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    if !base_url.ends_with('/') {
+        base_url.push('/');
+    }
+    let pick = |cli: &Vec<String>, file: &Vec<String>| {
+        if cli.is_empty() {
+            file.clone()
+        } else {
+            cli.clone()
+        }
+    };
+
+    let config = Config {
+        atlas: resolve(&cli.atlas, &from_file.atlas, "atlas"),
+        raw_atlas: resolve(&cli.raw_atlas, &from_file.raw_atlas, "raw_atlas"),
+        bdf: resolve(&cli.bdf, &from_file.bdf, "bdf"),
+        licenses: resolve(&cli.licenses, &from_file.licenses, "licenses"),
+        templates: resolve(&cli.templates, &from_file.templates, "templates"),
+        output: resolve(&cli.output, &from_file.output, "public"),
+        manifest: manifest_path,
+        base_url,
+        encodings: pick(&cli.encodings, &from_file.encodings),
+        families: pick(&cli.families, &from_file.families),
+    };
+    Ok((config, manifest))
+}
@@ -0,0 +1,369 @@
+use crate::bdf::{EgFile, MISC_LICENSE};
+use crate::extra_fonts::{load_atlas, Font, License, Provenance};
+use crate::fff::Fff;
+use crate::generator::encoding_index;
+use anyhow::{Context, Result};
+use embedded_graphics::mono_font::mapping::StrGlyphMapping;
+use std::path::Path;
+
+/// Code page 437, the character set of the original IBM PC.
+///
+/// Glyphs 0x01-0x1F are the pictographs shown by the PC in place of control characters,
+/// and 0x00 is the null character (rendered as blank).
+pub(crate) static CP437: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\0\0☺☻♥♦♣♠•◘○◙♂♀♪♫☼►◄↕‼¶§▬↨↑↓→←∟↔▲▼",
+        "\0 ~⌂",
+        "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒ",
+        "áíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐",
+        "└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀",
+        "αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}",
+    ),
+    0,
+);
+
+/// A CP437 font with all the sources of its glyphs.
+pub(crate) struct Cp437Font {
+    pub family: &'static str,
+    pub licenses: Vec<License>,
+    pub provenance: Vec<Provenance>,
+    pub data: Fff,
+}
+
+/// Full code page versions of the `ibm437` fonts.
+///
+/// The raw sheets have only glyphs 0x00-0x5F. Lowercase letters and the rest of ASCII
+/// come from the ASCII atlases (same as in the published ASCII fonts),
+/// box-drawing and block characters are drawn (see [`drawn_pixel`]),
+/// and other glyphs (accented letters, Greek, math) and the glyphs blank
+/// in the sheets come from the misc-fixed 5x8 font. Glyphs of both fonts stand
+/// on row 6 (the sheets leave the last row for descenders even though the declared
+/// baseline is 7), so rows are copied as is. Both have 1px strokes,
+/// and for the bold font vertical strokes are doubled.
+pub(crate) fn get_fonts(
+    raw_dir: &Path,
+    fonts: &[Font],
+    eg_files: &[EgFile],
+) -> Result<Vec<Cp437Font>> {
+    let index = encoding_index("cp437").unwrap();
+    let misc = eg_files
+        .iter()
+        .find(|f| f.family == "eg" && f.data.width == 5 && f.data.height == 8)
+        .context("misc-fixed 5x8 is missing")?;
+    let misc_provenance = Provenance {
+        conversion: "accented letters, Greek, and math symbols from bdf/5x8.bdf".into(),
+        ..misc.provenance.clone()
+    };
+    let chars: Vec<char> = CP437.chars().collect();
+    let sheets = [
+        (
+            "ibm437r",
+            "ibm437_8_8_regular.png",
+            "CP437 from raw_atlas/ibm437_8_8_regular.png, box-drawing and block characters drawn",
+            false,
+        ),
+        (
+            "ibm437b",
+            "ibm437_8_8_bold.png",
+            "CP437 from raw_atlas/ibm437_8_8_bold.png, box-drawing and block characters drawn",
+            true,
+        ),
+    ];
+    let mut result = Vec::new();
+    for (family, file_name, conversion, bold) in sheets {
+        let source = fonts.iter().find(|f| f.family == family).unwrap();
+        let mut ascii = Fff::from_mono(0, &source.font);
+        ascii.encoding = index as u8;
+        let sheet = Fff {
+            encoding: ascii.encoding,
+            width: ascii.width,
+            height: ascii.height,
+            baseline: ascii.baseline,
+            atlas_width: 16 * ascii.width,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: load_atlas(&raw_dir.join(file_name))
+                .context("load raw sheet")?
+                .data,
+            metadata: Vec::new(),
+        };
+        let own_pixel = |g: usize, x: u32, y: u32| match g {
+            0x00..=0x5F => sheet.pixel(g, x, y),
+            0x60..=0x7E => ascii.pixel(g - 0x20, x, y),
+            _ => drawn_pixel(g as u8, ascii.width, ascii.height, x, y),
+        };
+        let misc_glyphs: Vec<Option<usize>> = (0..256)
+            .map(|g| {
+                let blank =
+                    (0..ascii.height).all(|y| (0..ascii.width).all(|x| !own_pixel(g, x, y)));
+                chars
+                    .get(g)
+                    .filter(|_| blank)
+                    .and_then(|&c| misc.data.glyph_index(c))
+            })
+            .collect();
+        let misc_pixel = |glyph: usize, x: i32, y: i32| {
+            if bold {
+                misc.data.pixel_checked(glyph, x, y) || misc.data.pixel_checked(glyph, x - 1, y)
+            } else {
+                misc.data.pixel_checked(glyph, x - 1, y)
+            }
+        };
+        let font = Fff::from_glyphs(&ascii, 256, |g, x, y| match misc_glyphs[g] {
+            Some(glyph) => misc_pixel(glyph, x as i32, y as i32),
+            None => own_pixel(g, x, y),
+        });
+        let provenance = Provenance {
+            conversion: conversion.into(),
+            ..source.provenance.clone()
+        };
+        result.push(Cp437Font {
+            family,
+            licenses: vec![source.license.clone(), MISC_LICENSE.clone()],
+            provenance: vec![provenance, misc_provenance.clone()],
+            data: font,
+        });
+    }
+    Ok(result)
+}
+
+/// Line weights of box-drawing characters: up, down, left, right.
+///
+/// 0 is no line, 1 is a single line, 2 is a double line.
+fn box_arms(code: u8) -> Option<[u8; 4]> {
+    let arms = match code {
+        0xB3 => [1, 1, 0, 0],
+        0xB4 => [1, 1, 1, 0],
+        0xB5 => [1, 1, 2, 0],
+        0xB6 => [2, 2, 1, 0],
+        0xB7 => [0, 2, 1, 0],
+        0xB8 => [0, 1, 2, 0],
+        0xB9 => [2, 2, 2, 0],
+        0xBA => [2, 2, 0, 0],
+        0xBB => [0, 2, 2, 0],
+        0xBC => [2, 0, 2, 0],
+        0xBD => [2, 0, 1, 0],
+        0xBE => [1, 0, 2, 0],
+        0xBF => [0, 1, 1, 0],
+        0xC0 => [1, 0, 0, 1],
+        0xC1 => [1, 0, 1, 1],
+        0xC2 => [0, 1, 1, 1],
+        0xC3 => [1, 1, 0, 1],
+        0xC4 => [0, 0, 1, 1],
+        0xC5 => [1, 1, 1, 1],
+        0xC6 => [1, 1, 0, 2],
+        0xC7 => [2, 2, 0, 1],
+        0xC8 => [2, 0, 0, 2],
+        0xC9 => [0, 2, 0, 2],
+        0xCA => [2, 0, 2, 2],
+        0xCB => [0, 2, 2, 2],
+        0xCC => [2, 2, 0, 2],
+        0xCD => [0, 0, 2, 2],
+        0xCE => [2, 2, 2, 2],
+        0xCF => [1, 0, 2, 2],
+        0xD0 => [2, 0, 1, 1],
+        0xD1 => [0, 1, 2, 2],
+        0xD2 => [0, 2, 1, 1],
+        0xD3 => [2, 0, 0, 1],
+        0xD4 => [1, 0, 0, 2],
+        0xD5 => [0, 1, 0, 2],
+        0xD6 => [0, 2, 0, 1],
+        0xD7 => [2, 2, 1, 1],
+        0xD8 => [1, 1, 2, 2],
+        0xD9 => [1, 0, 1, 0],
+        0xDA => [0, 1, 0, 1],
+        _ => return None,
+    };
+    Some(arms)
+}
+
+/// The CP437 code of the character if it is one of the glyphs drawn by [`drawn_pixel`].
+///
+/// Used to draw box-drawing characters in other encodings.
+pub(crate) fn drawn_code(c: char) -> Option<u8> {
+    let code = CP437.chars().position(|p| p == c)? as u8;
+    match code {
+        0xB0..=0xDF | 0xFE => Some(code),
+        _ => None,
+    }
+}
+
+/// Pixels of box-drawing, block, and shade characters.
+///
+/// These glyphs are pure geometry, so they are drawn for any glyph size
+/// (like terminal emulators do) and line up seamlessly with neighbor glyphs.
+pub(crate) fn drawn_pixel(code: u8, width: u32, height: u32, x: u32, y: u32) -> bool {
+    match code {
+        0xB0 => (x + 2 * y) % 4 == 2,
+        0xB1 => (x + y).is_multiple_of(2),
+        0xB2 => !(x + 2 * y).is_multiple_of(4),
+        0xDB => true,
+        0xDC => y >= height / 2,
+        0xDD => x < width / 2,
+        0xDE => x >= width / 2,
+        0xDF => y < height / 2,
+        0xFE => {
+            let (dx, dy) = (width / 4, height / 4);
+            x >= dx && x < width - dx && y >= dy && y < height - dy
+        }
+        _ => match box_arms(code) {
+            Some(arms) => box_pixel(arms, width, height, x, y),
+            None => false,
+        },
+    }
+}
+
+fn box_pixel(arms: [u8; 4], width: u32, height: u32, x: u32, y: u32) -> bool {
+    let [up, down, left, right] = arms;
+    let cx = (width as i32 - 1) / 2;
+    let cy = (height as i32 - 1) / 2;
+    let (x, y) = (x as i32, y as i32);
+
+    // Vertical arms. Offsets of lines from the center are -1 (left) and 1 (right)
+    // for double lines and 0 for a single line. Each line goes from the edge
+    // to the center shifted by `arm_end` toward (-1) or past (1) the center.
+    for (weight, opposite, is_up) in [(up, down, true), (down, up, false)] {
+        for &offset in line_offsets(weight) {
+            if x != cx + offset {
+                continue;
+            }
+            let end = arm_end(weight, offset, opposite, [left, right]);
+            let hit = if is_up { y <= cy + end } else { y >= cy - end };
+            if hit {
+                return true;
+            }
+        }
+    }
+    // Horizontal arms, the same with axes swapped.
+    for (weight, opposite, is_left) in [(left, right, true), (right, left, false)] {
+        for &offset in line_offsets(weight) {
+            if y != cy + offset {
+                continue;
+            }
+            let end = arm_end(weight, offset, opposite, [up, down]);
+            let hit = if is_left {
+                x <= cx + end
+            } else {
+                x >= cx - end
+            };
+            if hit {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn line_offsets(weight: u8) -> &'static [i32] {
+    match weight {
+        0 => &[],
+        1 => &[0],
+        _ => &[-1, 1],
+    }
+}
+
+/// Where a line of an arm stops, relative to the center.
+///
+/// `across` are weights of the two perpendicular arms, the one on the negative side first.
+fn arm_end(weight: u8, offset: i32, opposite: u8, across: [u8; 2]) -> i32 {
+    let [neg, pos] = across;
+    if weight == 1 {
+        return if opposite > 0 {
+            // continues as the opposite arm
+            0
+        } else if neg == 2 && pos == 2 {
+            // stops at the near line of a double line passing through
+            -1
+        } else if neg == 2 || pos == 2 {
+            // reaches the far line of a double corner
+            1
+        } else {
+            0
+        };
+    }
+    let (same_side, other_side) = if offset < 0 { (neg, pos) } else { (pos, neg) };
+    match same_side {
+        2 => -1,
+        1 if opposite == 2 => 1,
+        1 => 0,
+        _ if opposite == 2 => 1,
+        _ if other_side == 2 => 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bdf::eg_files;
+    use crate::extra_fonts::load_atlases;
+
+    fn render(code: u8) -> String {
+        let mut result = String::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                result.push(if drawn_pixel(code, 8, 8, x, y) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    #[test]
+    fn test_mapping() {
+        assert_eq!(CP437.chars().count(), 256);
+        let chars: Vec<char> = CP437.chars().collect();
+        assert_eq!(chars[0x41], 'A');
+        assert_eq!(chars[0xC9], '╔');
+        assert_eq!(chars[0xFF], '\u{a0}');
+    }
+
+    #[test]
+    fn test_all_glyphs_drawn() {
+        let atlases = load_atlases(Path::new("atlas")).unwrap();
+        let fonts = crate::extra_fonts::get_fonts(&atlases);
+        let eg_files = eg_files(Path::new("bdf")).unwrap();
+        for font in get_fonts(Path::new("raw_atlas"), &fonts, &eg_files).unwrap() {
+            let data = &font.data;
+            for (c, glyph) in data.visible_glyphs() {
+                let drawn =
+                    (0..data.height).any(|y| (0..data.width).any(|x| data.pixel(glyph, x, y)));
+                assert!(drawn || c.is_whitespace(), "{}: {c} is blank", font.family);
+            }
+        }
+    }
+
+    #[test]
+    fn test_double_corner() {
+        let expected = concat!(
+            "........\n",
+            "........\n",
+            "..######\n",
+            "..#.....\n",
+            "..#.####\n",
+            "..#.#...\n",
+            "..#.#...\n",
+            "..#.#...\n",
+        );
+        assert_eq!(render(0xC9), expected);
+    }
+
+    #[test]
+    fn test_mixed_cross() {
+        let expected = concat!(
+            "..#.#...\n",
+            "..#.#...\n",
+            "..#.#...\n",
+            "########\n",
+            "..#.#...\n",
+            "..#.#...\n",
+            "..#.#...\n",
+            "..#.#...\n",
+        );
+        assert_eq!(render(0xD7), expected);
+    }
+}
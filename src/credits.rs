@@ -0,0 +1,55 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font};
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Credits for the fonts that games can include verbatim.
+///
+/// A block per font with the authors, upstream sources, and licenses.
+/// Fonts with the same credits (like the same font in several encodings)
+/// share the block.
+pub(crate) fn credits(fonts: &[&Font]) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    for font in fonts {
+        let block = font_credits(font);
+        if !blocks.contains(&block) {
+            blocks.push(block);
+        }
+    }
+    blocks.join("\n")
+}
+
+fn font_credits(font: &Font) -> String {
+    let mut out = String::new();
+    writeln!(out, "{} {}x{} font", font.family, font.width, font.height).unwrap();
+    if let (Some(from), Some(transform)) = (&font.derived_from, &font.transform) {
+        writeln!(out, "  {} of {from}", transform.label()).unwrap();
+    }
+    for source in &font.provenance {
+        match &source.version {
+            Some(version) => writeln!(out, "  by {} ({version})", source.author).unwrap(),
+            None => writeln!(out, "  by {}", source.author).unwrap(),
+        }
+        writeln!(out, "  {}", source.url).unwrap();
+    }
+    for copyright in font.licenses.iter().filter_map(|l| l.copyright.as_ref()) {
+        writeln!(out, "  {copyright}").unwrap();
+    }
+    let licenses: Vec<&str> = font.licenses.iter().map(|l| &*l.spdx).collect();
+    writeln!(out, "  license: {}", licenses.join(", ")).unwrap();
+    out
+}
+
+/// Write `CREDITS` for the whole catalog into the site root.
+///
+/// Returns 1 if the file was updated.
+pub(crate) fn write_credits(root: &Path, fonts: &[Font], cache: &BuildCache) -> Result<usize> {
+    let path = root.join("CREDITS");
+    let text = credits(&unique_files(fonts));
+    if cache.is_fresh(&path, hash_of(&text)) {
+        return Ok(0);
+    }
+    std::fs::write(&path, text).context("write credits")?;
+    Ok(1)
+}
@@ -0,0 +1,308 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font};
+use crate::fff::Fff;
+use crate::image::write_indexed;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The version of the screen layout in `render_device`.
+///
+/// It's a part of the cache key, so bump it when changing the layout.
+const LAYOUT_VERSION: u32 = 1;
+
+/// The size of the Firefly Zero screen.
+pub(crate) const SCREEN_WIDTH: u32 = 240;
+pub(crate) const SCREEN_HEIGHT: u32 = 160;
+
+/// The gap between the screen edges and the UI.
+const PADDING: u32 = 4;
+
+/// The gap between the dialog box border and its text.
+const DIALOG_PADDING: u32 = 3;
+
+/// Colors of device previews on the site.
+const FOREGROUND: Color = Color::White;
+const BACKGROUND: Color = Color::DarkBlue;
+
+/// The scale of the enlarged device previews and comparison screens on the site.
+pub(crate) const SCALE: u32 = 3;
+
+const HEALTH: &str = "HP 87/100";
+const SCORE: &str = "SCORE 004250";
+const MENU: &[&str] = &["New game", "Continue", "Options", "Quit"];
+/// The highlighted menu item.
+const SELECTED: usize = 1;
+const DIALOG: &str = "Welcome, traveler! The bridge to the north is out, \
+    so talk to the smith in the village before you go.";
+/// The most lines of text the dialog box shows.
+const DIALOG_LINES: u32 = 3;
+
+/// The Firefly Zero palette (SWEETIE-16), in the order of SDK colors.
+#[derive(Copy, Clone, clap::ValueEnum)]
+pub(crate) enum Color {
+    Black,
+    Purple,
+    Red,
+    Orange,
+    Yellow,
+    LightGreen,
+    Green,
+    DarkGreen,
+    DarkBlue,
+    Blue,
+    LightBlue,
+    Cyan,
+    White,
+    LightGray,
+    Gray,
+    DarkGray,
+}
+
+pub(crate) static PALETTE: [[u8; 3]; 16] = [
+    [0x1a, 0x1c, 0x2c],
+    [0x5d, 0x27, 0x5d],
+    [0xb1, 0x3e, 0x53],
+    [0xef, 0x7d, 0x57],
+    [0xff, 0xcd, 0x75],
+    [0xa7, 0xf0, 0x70],
+    [0x38, 0xb7, 0x64],
+    [0x25, 0x71, 0x79],
+    [0x29, 0x36, 0x6f],
+    [0x3b, 0x5d, 0xc9],
+    [0x41, 0xa6, 0xf6],
+    [0x73, 0xef, 0xf7],
+    [0xf4, 0xf4, 0xf4],
+    [0x94, 0xb0, 0xc2],
+    [0x56, 0x6c, 0x86],
+    [0x33, 0x3c, 0x57],
+];
+
+#[derive(clap::Args)]
+pub(crate) struct DeviceArgs {
+    /// The font to preview (FFF).
+    font: PathBuf,
+
+    /// Where to save the screen at 1x (PNG).
+    ///
+    /// The scaled screen goes next to it, like `screen.3x.png` for `screen.png`.
+    output: PathBuf,
+
+    /// The color of text and UI elements.
+    #[arg(long, value_enum, default_value = "white")]
+    foreground: Color,
+
+    /// The color of the screen.
+    #[arg(long, value_enum, default_value = "dark-blue")]
+    background: Color,
+
+    /// The scale of the enlarged screen.
+    #[arg(long, default_value_t = SCALE, value_parser = clap::value_parser!(u32).range(2..=8))]
+    scale: u32,
+}
+
+/// Render a mock game screen with the font, at 1x and scaled.
+pub(crate) fn cmd_device(args: &DeviceArgs) -> Result<()> {
+    let font = Fff::read(&args.font)?;
+    let screen = render_device(&font, args.foreground, args.background);
+    let scaled = scaled_path(&args.output, args.scale);
+    write_screen(&screen, 1, &args.output)?;
+    write_screen(&screen, args.scale, &scaled)?;
+    println!("Saved {} and {}", args.output.display(), scaled.display());
+    Ok(())
+}
+
+/// The path of the scaled screen: `screen.png` becomes `screen.3x.png`.
+pub(crate) fn scaled_path(path: &Path, scale: u32) -> PathBuf {
+    path.with_extension(format!("{scale}x.png"))
+}
+
+fn write_screen(screen: &[u8], scale: u32, path: &Path) -> Result<()> {
+    write_indexed(screen, SCREEN_WIDTH, SCREEN_HEIGHT, &PALETTE, scale, path)
+        .with_context(|| format!("write {}", path.display()))
+}
+
+/// Render device previews for all catalog fonts except rotated ones.
+///
+/// Returns how many images were updated.
+pub(crate) fn devices_to_images(
+    fonts: &[Font],
+    out_dir: &Path,
+    cache: &BuildCache,
+) -> Result<usize> {
+    let fonts: Vec<&Font> = unique_files(fonts)
+        .into_iter()
+        .filter(|f| !f.rotated)
+        .collect();
+    for font in &fonts {
+        fs::create_dir_all(out_dir.join(font.encoding)).context("create devices dir")?;
+    }
+    let updated = AtomicUsize::new(0);
+    fonts.par_iter().try_for_each(|font| -> Result<()> {
+        let path = out_dir
+            .join(font.encoding)
+            .join(format!("{}.png", font.name()));
+        let hash = hash_of(&(font.data.to_bytes()?, render_inputs()));
+        let scaled = scaled_path(&path, SCALE);
+        // Both images are checked, so that neither goes missing or stale.
+        let fresh = cache.is_fresh(&path, hash) & cache.is_fresh(&scaled, hash);
+        if !fresh {
+            let screen = render_device(&font.data, FOREGROUND, BACKGROUND);
+            write_screen(&screen, 1, &path)?;
+            write_screen(&screen, SCALE, &scaled)?;
+            updated.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    })?;
+    Ok(updated.into_inner())
+}
+
+/// Everything besides the font that the device previews depend on.
+fn render_inputs() -> impl std::hash::Hash {
+    (
+        LAYOUT_VERSION,
+        (
+            PADDING,
+            DIALOG_PADDING,
+            FOREGROUND as u8,
+            BACKGROUND as u8,
+            SCALE,
+        ),
+        (HEALTH, SCORE, MENU, SELECTED, DIALOG, DIALOG_LINES),
+        PALETTE,
+    )
+}
+
+/// A typical game UI drawn with the font: HUD numbers at the top,
+/// a menu with the highlighted item, and a dialog box at the bottom.
+///
+/// Returns palette indices of the screen pixels, row by row.
+/// UI elements that don't fit are cut off or skipped.
+pub(crate) fn render_device(font: &Fff, foreground: Color, background: Color) -> Vec<u8> {
+    let mut screen = Screen {
+        pixels: vec![background as u8; (SCREEN_WIDTH * SCREEN_HEIGHT) as usize],
+    };
+    let (fg, bg) = (foreground as u8, background as u8);
+    let line_height = font.height + 1;
+
+    // HUD: health on the left, score on the right, and a line below.
+    screen.draw_text(font, HEALTH, PADDING, PADDING, fg);
+    let score_width = SCORE.len() as u32 * font.width;
+    let health_width = HEALTH.len() as u32 * font.width;
+    if PADDING + health_width + font.width + score_width <= SCREEN_WIDTH - PADDING {
+        let x = SCREEN_WIDTH - PADDING - score_width;
+        screen.draw_text(font, SCORE, x, PADDING, fg);
+    }
+    let hud_bottom = PADDING + font.height + 2;
+    screen.fill(0, hud_bottom, SCREEN_WIDTH, 1, fg);
+
+    // Dialog: a framed box with the text wrapped by words.
+    let columns = (SCREEN_WIDTH - 2 * PADDING - 2 * DIALOG_PADDING - 2) / font.width;
+    let lines = wrap_words(DIALOG, columns.max(1) as usize);
+    let shown = (lines.len() as u32).min(DIALOG_LINES);
+    let dialog_height = shown * line_height - 1 + 2 * DIALOG_PADDING + 2;
+    let dialog_top = SCREEN_HEIGHT.saturating_sub(PADDING + dialog_height);
+    let dialog_width = SCREEN_WIDTH - 2 * PADDING;
+    screen.fill(PADDING, dialog_top, dialog_width, dialog_height, fg);
+    screen.fill(
+        PADDING + 1,
+        dialog_top + 1,
+        dialog_width - 2,
+        dialog_height - 2,
+        bg,
+    );
+    for (i, line) in lines.iter().take(shown as usize).enumerate() {
+        let y = dialog_top + 1 + DIALOG_PADDING + i as u32 * line_height;
+        screen.draw_text(font, line, PADDING + 1 + DIALOG_PADDING, y, fg);
+    }
+
+    // Menu: the items that fit between the HUD and the dialog.
+    let item_height = font.height + 2;
+    let menu_width = MENU.iter().map(|i| i.len()).max().unwrap_or(0) as u32 * font.width + 2;
+    let mut y = hud_bottom + 1 + PADDING;
+    for (i, item) in MENU.iter().enumerate() {
+        if y + item_height + PADDING > dialog_top {
+            break;
+        }
+        let color = if i == SELECTED {
+            screen.fill(PADDING, y, menu_width, item_height, fg);
+            bg
+        } else {
+            fg
+        };
+        screen.draw_text(font, item, PADDING + 1, y + 1, color);
+        y += item_height;
+    }
+    screen.pixels
+}
+
+/// Split the text into lines of at most the given number of characters.
+///
+/// Words longer than a line stay whole and are cut off when drawn.
+pub(crate) fn wrap_words(text: &str, columns: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![String::new()];
+    for word in text.split(' ') {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && line.len() + 1 + word.len() > columns {
+            lines.push(String::new());
+        }
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines
+}
+
+/// Palette indices of the screen pixels.
+struct Screen {
+    pixels: Vec<u8>,
+}
+
+impl Screen {
+    fn set(&mut self, x: u32, y: u32, color: u8) {
+        if x < SCREEN_WIDTH && y < SCREEN_HEIGHT {
+            self.pixels[(y * SCREEN_WIDTH + x) as usize] = color;
+        }
+    }
+
+    fn fill(&mut self, left: u32, top: u32, width: u32, height: u32, color: u8) {
+        for y in top..top + height {
+            for x in left..left + width {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Draw a line of text the same way the device does: a glyph cell per character,
+    /// and only set pixels of glyphs are drawn.
+    fn draw_text(&mut self, font: &Fff, text: &str, left: u32, top: u32, color: u8) {
+        for (column, c) in text.chars().enumerate() {
+            let Some(glyph) = font.glyph_index(c) else {
+                continue;
+            };
+            let glyph_left = left + column as u32 * font.width;
+            for y in 0..font.height {
+                for x in 0..font.width {
+                    if font.pixel(glyph, x, y) {
+                        self.set(glyph_left + x, top + y, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_words() {
+        let lines = wrap_words("aa bb cc dddddd e", 5);
+        assert_eq!(lines, ["aa bb", "cc", "dddddd", "e"]);
+    }
+}
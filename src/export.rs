@@ -0,0 +1,208 @@
+use crate::fff::Fff;
+use crate::generator::encoding_name;
+use crate::order::order_text;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Colors of the exported sheet: white glyphs on black, like in atlases,
+/// and cell borders that `sheet` takes for the background.
+const BACKGROUND: [u8; 3] = [0x00, 0x00, 0x00];
+const FOREGROUND: [u8; 3] = [0xFF, 0xFF, 0xFF];
+const BORDER: [u8; 3] = [0x40, 0x40, 0x40];
+
+#[derive(clap::Args)]
+pub(crate) struct ExportArgs {
+    /// The font to export (FFF).
+    font: PathBuf,
+
+    /// Where to save the result: a BDF file (`.bdf`) or a sprite sheet (`.png`).
+    ///
+    /// The sheet comes with its glyph order (`.txt`) and metrics (`.json`) next to it.
+    output: PathBuf,
+}
+
+/// Metrics of an exported sheet, saved next to it.
+///
+/// `sheet` carries them over to the atlas, and `import` takes the baseline from there.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SheetMetrics {
+    pub encoding: String,
+    pub cell_width: u32,
+    pub cell_height: u32,
+    /// The row of the baseline, counting from the top of the cell.
+    pub baseline: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub spacing: u32,
+    pub margin: u32,
+    pub glyphs: usize,
+}
+
+impl SheetMetrics {
+    /// Read the metrics saved next to the sheet, if there are any.
+    pub fn read(sheet: &Path) -> Result<Option<Self>> {
+        let path = sheet.with_extension("json");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(path).context("read sheet metrics")?;
+        let metrics = serde_json::from_str(&raw).context("parse sheet metrics")?;
+        Ok(Some(metrics))
+    }
+
+    pub fn write(&self, sheet: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("serialize sheet metrics")?;
+        std::fs::write(sheet.with_extension("json"), json).context("write sheet metrics")
+    }
+}
+
+pub(crate) fn cmd_export(args: &ExportArgs) -> Result<()> {
+    let font = Fff::read(&args.font)?;
+    let glyphs = font.visible_glyphs();
+    match args.output.extension().and_then(|e| e.to_str()) {
+        Some("bdf") => {
+            let stem = args.font.file_stem().unwrap_or_default().to_string_lossy();
+            // The family name without the size, like in `pico8_4x6.fff`.
+            let family = stem.rsplit_once('_').map_or(&*stem, |(family, _)| family);
+            let bdf = to_bdf(&font, family);
+            std::fs::write(&args.output, bdf).context("write BDF file")?;
+        }
+        Some("png") => write_sheet(&font, &args.output)?,
+        _ => bail!("unknown export format, expected .bdf or .png"),
+    }
+    println!("Exported {} glyphs", glyphs.len());
+    Ok(())
+}
+
+/// Convert the font into BDF.
+///
+/// Glyphs are indexed by Unicode code points. Each glyph has the whole cell
+/// as the bounding box, with the baseline of the font, and advances by the cell width.
+/// The resolution is 72 DPI, so the point size is the same as the pixel size.
+pub(crate) fn to_bdf(font: &Fff, family: &str) -> String {
+    let (width, height) = (font.width, font.height);
+    let descent = height as i32 - 1 - font.baseline as i32;
+    let ascent = height as i32 - descent;
+    let glyphs = font.visible_glyphs();
+    // XLFD fields are separated by dashes, so they can't be in the family name.
+    let family = family.replace('-', " ");
+
+    let mut out = String::new();
+    let mut line = |s: String| {
+        out.push_str(&s);
+        out.push('\n');
+    };
+    line("STARTFONT 2.1".into());
+    line(format!(
+        "FONT -firefly-{family}-medium-r-normal--{height}-{}-72-72-c-{}-iso10646-1",
+        height * 10,
+        width * 10,
+    ));
+    line(format!("SIZE {height} 72 72"));
+    line(format!("FONTBOUNDINGBOX {width} {height} 0 {}", -descent));
+    line("STARTPROPERTIES 5".into());
+    line(format!("FONT_ASCENT {ascent}"));
+    line(format!("FONT_DESCENT {descent}"));
+    line("SPACING \"C\"".into());
+    line("CHARSET_REGISTRY \"ISO10646\"".into());
+    line("CHARSET_ENCODING \"1\"".into());
+    line("ENDPROPERTIES".into());
+    line(format!("CHARS {}", glyphs.len()));
+    for (c, glyph) in glyphs {
+        let code = u32::from(c);
+        line(format!("STARTCHAR U+{code:04X}"));
+        line(format!("ENCODING {code}"));
+        line(format!("SWIDTH {} 0", width * 1000 / height.max(1)));
+        line(format!("DWIDTH {width} 0"));
+        line(format!("BBX {width} {height} 0 {}", -descent));
+        line("BITMAP".into());
+        for y in 0..height {
+            let mut row = vec![0u8; width.div_ceil(8) as usize];
+            for x in 0..width {
+                if font.pixel(glyph, x, y) {
+                    row[x as usize / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            let mut hex = String::new();
+            for byte in row {
+                write!(hex, "{byte:02X}").unwrap();
+            }
+            line(hex);
+        }
+        line("ENDCHAR".into());
+    }
+    line("ENDFONT".into());
+    out
+}
+
+/// Save glyphs as a sprite sheet, 16 per row, with 1px borders around cells.
+///
+/// The glyph order and metrics are saved next to it, so that the sheet
+/// can be edited and imported back with `sheet`.
+fn write_sheet(font: &Fff, path: &Path) -> Result<()> {
+    let glyphs = font.visible_glyphs();
+    let columns = 16;
+    let rows = (glyphs.len() as u32).div_ceil(columns).max(1);
+    let (pitch_x, pitch_y) = (font.width + 1, font.height + 1);
+    let img = image::RgbImage::from_fn(1 + columns * pitch_x, 1 + rows * pitch_y, |x, y| {
+        if x % pitch_x == 0 || y % pitch_y == 0 {
+            return image::Rgb(BORDER);
+        }
+        let index = (y / pitch_y * columns + x / pitch_x) as usize;
+        let (x, y) = (x % pitch_x - 1, y % pitch_y - 1);
+        match glyphs.get(index) {
+            Some(&(_, glyph)) if font.pixel(glyph, x, y) => image::Rgb(FOREGROUND),
+            _ => image::Rgb(BACKGROUND),
+        }
+    });
+    img.save(path).context("write sheet")?;
+
+    let chars: Vec<char> = glyphs.iter().map(|&(c, _)| c).collect();
+    std::fs::write(path.with_extension("txt"), order_text(&chars)).context("write glyph order")?;
+    let metrics = SheetMetrics {
+        encoding: encoding_name(usize::from(font.encoding))
+            .unwrap_or("unicode")
+            .to_string(),
+        cell_width: font.width,
+        cell_height: font.height,
+        baseline: font.baseline,
+        columns,
+        rows,
+        spacing: 1,
+        margin: 1,
+        glyphs: glyphs.len(),
+    };
+    metrics.write(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bdf;
+
+    #[test]
+    fn test_bdf_roundtrip() {
+        let template = Fff {
+            encoding: 0,
+            width: 5,
+            height: 7,
+            baseline: 5,
+            atlas_width: 0,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        let font = Fff::from_glyphs(&template, 95, |g, x, y| {
+            (g as u32 * 7 + x * y).is_multiple_of(5)
+        });
+        let raw = to_bdf(&font, "test-bold");
+        assert!(raw.contains("FONT -firefly-test bold-medium-r-normal--7-70-72-72-c-50-"));
+        assert!(raw.contains("STARTCHAR U+0041\nENCODING 65\n"));
+        let parsed = bdf::parse(&raw).unwrap().convert("ascii").unwrap();
+        assert_eq!(parsed.baseline, font.baseline);
+        assert_eq!(parsed.atlas, font.atlas);
+    }
+}
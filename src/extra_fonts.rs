@@ -1,5 +1,8 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::borrow::Cow;
+use std::{collections::HashMap, path::Path};
 
+use crate::licenses::license_info;
+use crate::order::GlyphOrder;
 use anyhow::{Context, Result};
 use embedded_graphics::{
     image::ImageRaw,
@@ -8,215 +11,346 @@ use embedded_graphics::{
 };
 use serde::Serialize;
 
-type Atlases = HashMap<&'static str, Vec<u8>>;
+pub(crate) type Atlases = HashMap<&'static str, Atlas>;
+
+/// A decoded atlas image.
+pub(crate) struct Atlas {
+    /// 1-bit packed pixels, row by row.
+    pub data: Vec<u8>,
+    pub width: u32,
+    /// The order of glyphs, from the text file with the same name as the image
+    /// (like `pico8_4x6.txt` for `pico8_4x6.png`) or, if there is none, ASCII.
+    pub order: GlyphOrder,
+}
 
 pub(crate) struct Font<'a> {
     pub family: &'static str,
     pub font: MonoFont<'a>,
     pub license: License,
+    pub provenance: Provenance,
+}
+
+/// Where a font comes from and how it got into the catalog.
+#[derive(Clone, PartialEq, Serialize)]
+pub(crate) struct Provenance {
+    pub author: Cow<'static, str>,
+    /// The upstream source of the font.
+    pub url: Cow<'static, str>,
+    /// The upstream version or release date, if known.
+    pub version: Option<Cow<'static, str>>,
+    /// How the catalog font is made from the upstream one.
+    pub conversion: Cow<'static, str>,
 }
 
-#[derive(Copy, Clone, Serialize)]
+#[derive(Clone, PartialEq, Serialize)]
 pub(crate) struct License {
     /// https://spdx.org/licenses/
-    pub spdx: &'static str,
-    pub url: &'static str,
+    pub spdx: Cow<'static, str>,
+    /// The license text published with the upstream font, if there is one.
+    /// Without it, the bundled `licenses/<SPDX ID>.txt` is linked instead.
+    pub url: Option<Cow<'static, str>>,
+    /// The copyright notice that copies of the font must keep, if the license requires one.
+    pub copyright: Option<Cow<'static, str>>,
 }
 
+impl License {
+    /// If works that include the font must be shared under the same license.
+    ///
+    /// Unknown licenses fail the build before this is called (see `src/licenses.rs`).
+    pub fn is_copyleft(&self) -> bool {
+        license_info(&self.spdx).is_ok_and(|info| info.copyleft)
+    }
+}
+
+pub(crate) static EG_LICENSE: License = License {
+    spdx: Cow::Borrowed("MIT"),
+    url: Some(Cow::Borrowed(
+        "https://github.com/embedded-graphics/embedded-graphics/blob/master/LICENSE-MIT",
+    )),
+    copyright: Some(Cow::Borrowed("Copyright (c) 2020 James Waples")),
+};
+
+/// The upstream grant (https://github.com/oidoid/mem/blob/main/license.text)
+/// is the AGPL v3 text alone, without the "or any later version" notice
+/// that would allow later versions, so the font is under version 3 only.
+static MEM_LICENSE: License = License {
+    spdx: Cow::Borrowed("AGPL-3.0-only"),
+    url: Some(Cow::Borrowed(
+        "https://github.com/oidoid/mem/blob/main/license.text",
+    )),
+    copyright: Some(Cow::Borrowed("Copyright (c) oidoid")),
+};
+
+pub(crate) static EG_PROVENANCE: Provenance = Provenance {
+    author: Cow::Borrowed("embedded-graphics contributors"),
+    url: Cow::Borrowed("https://github.com/embedded-graphics/embedded-graphics"),
+    // Pinned in Cargo.toml, checked by `test_eg_version`.
+    version: Some(Cow::Borrowed("0.8.1")),
+    conversion: Cow::Borrowed("converted from the embedded-graphics MonoFont"),
+};
+
 /// Convert atlases into embedded-graphics fonts.
 pub(crate) fn get_fonts(atlases: &Atlases) -> Vec<Font<'_>> {
     let ibm437b = MonoFont {
-        image: ImageRaw::new(&atlases["ibm437b"], 128),
+        image: ImageRaw::new(&atlases["ibm437b"].data, atlases["ibm437b"].width),
         character_size: Size::new(8, 8),
         character_spacing: 0,
         baseline: 7,
         strikethrough: DecorationDimensions::new(4, 1),
         underline: DecorationDimensions::new(8, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["ibm437b"].order,
     };
     let ibm437r = MonoFont {
-        image: ImageRaw::new(&atlases["ibm437r"], 128),
+        image: ImageRaw::new(&atlases["ibm437r"].data, atlases["ibm437r"].width),
         character_size: Size::new(8, 8),
         character_spacing: 0,
         baseline: 7,
         strikethrough: DecorationDimensions::new(4, 1),
         underline: DecorationDimensions::new(8, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["ibm437r"].order,
     };
     let pico8 = MonoFont {
-        image: ImageRaw::new(&atlases["pico8"], 64),
+        image: ImageRaw::new(&atlases["pico8"].data, atlases["pico8"].width),
         character_size: Size::new(4, 6),
         character_spacing: 0,
         baseline: 5,
         strikethrough: DecorationDimensions::new(3, 1),
         underline: DecorationDimensions::new(6, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["pico8"].order,
     };
     let kenney11 = MonoFont {
-        image: ImageRaw::new(&atlases["kenney11"], 176),
+        image: ImageRaw::new(&atlases["kenney11"].data, atlases["kenney11"].width),
         character_size: Size::new(11, 14),
         character_spacing: 0,
         baseline: 14,
         strikethrough: DecorationDimensions::new(7, 2),
         underline: DecorationDimensions::new(15, 2),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["kenney11"].order,
     };
     let kenney16 = MonoFont {
-        image: ImageRaw::new(&atlases["kenney16"], 256),
+        image: ImageRaw::new(&atlases["kenney16"].data, atlases["kenney16"].width),
         character_size: Size::new(16, 16),
         character_spacing: 0,
         baseline: 14,
         strikethrough: DecorationDimensions::new(8, 2),
         underline: DecorationDimensions::new(15, 2),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["kenney16"].order,
     };
     let profont = MonoFont {
-        image: ImageRaw::new(&atlases["profont"], 80),
+        image: ImageRaw::new(&atlases["profont"].data, atlases["profont"].width),
         character_size: Size::new(5, 9),
         character_spacing: 0,
         baseline: 9,
         strikethrough: DecorationDimensions::new(5, 1),
         underline: DecorationDimensions::new(10, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["profont"].order,
     };
     let mem44 = MonoFont {
-        image: ImageRaw::new(&atlases["mem44"], 64),
+        image: ImageRaw::new(&atlases["mem44"].data, atlases["mem44"].width),
         character_size: Size::new(4, 4),
         character_spacing: 0,
         baseline: 3,
         strikethrough: DecorationDimensions::new(2, 1),
         underline: DecorationDimensions::new(4, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["mem44"].order,
     };
     let mem45 = MonoFont {
-        image: ImageRaw::new(&atlases["mem45"], 64),
+        image: ImageRaw::new(&atlases["mem45"].data, atlases["mem45"].width),
         character_size: Size::new(4, 5),
         character_spacing: 0,
         baseline: 4,
         strikethrough: DecorationDimensions::new(2, 1),
         underline: DecorationDimensions::new(4, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["mem45"].order,
     };
     let mem55 = MonoFont {
-        image: ImageRaw::new(&atlases["mem55"], 80),
+        image: ImageRaw::new(&atlases["mem55"].data, atlases["mem55"].width),
         character_size: Size::new(5, 5),
         character_spacing: 0,
         baseline: 4,
         strikethrough: DecorationDimensions::new(2, 1),
         underline: DecorationDimensions::new(5, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["mem55"].order,
     };
     let sixel = MonoFont {
-        image: ImageRaw::new(&atlases["sixel"], 16),
+        image: ImageRaw::new(&atlases["sixel"].data, atlases["sixel"].width),
         character_size: Size::new(1, 6),
         character_spacing: 0,
         baseline: 6,
         strikethrough: DecorationDimensions::new(3, 1),
         underline: DecorationDimensions::new(7, 1),
-        glyph_mapping: &embedded_graphics::mono_font::mapping::ASCII,
+        glyph_mapping: &atlases["sixel"].order,
     };
     vec![
         Font {
             family: "pico8",
             font: pico8,
             license: License {
-                spdx: "CC0-1.0",
-                url: "https://www.lexaloffle.com/pico-8.php?page=faq",
+                spdx: "CC0-1.0".into(),
+                url: None,
+                copyright: None,
+            },
+            provenance: Provenance {
+                author: "Lexaloffle Games".into(),
+                url: "https://www.lexaloffle.com/pico-8.php".into(),
+                version: None,
+                conversion: "PICO-8 font from atlas/pico8_4x6.png".into(),
             },
         },
         Font {
             family: "profont",
             font: profont,
             license: License {
-                spdx: "MIT",
-                url: "https://tobiasjung.name/profont/",
+                spdx: "MIT".into(),
+                url: None,
+                copyright: Some(
+                    "Copyright (c) Andrew Welch, Carl Osterwald, Jon Pugh, and Tobias Jung".into(),
+                ),
+            },
+            provenance: Provenance {
+                author: "Andrew Welch, Carl Osterwald, Jon Pugh, and Tobias Jung".into(),
+                url: "https://tobiasjung.name/profont/".into(),
+                version: None,
+                conversion: "ProFont 9pt cropped to 5x9 from raw_atlas/ProFont9Point.png".into(),
             },
         },
         Font {
             family: "ibm437b",
             font: ibm437b,
             license: License {
-                spdx: "MIT",
-                url: "https://github.com/sbechet/ibm437/blob/master/LICENCE",
+                spdx: "MIT".into(),
+                url: Some("https://github.com/sbechet/ibm437/blob/master/LICENCE".into()),
+                copyright: Some("Copyright (c) Sebastien Bechet".into()),
+            },
+            provenance: Provenance {
+                author: "Sebastien Bechet".into(),
+                url: "https://github.com/sbechet/ibm437".into(),
+                version: None,
+                conversion: "ASCII part of the IBM PC 8x8 bold font, from atlas/ibm437b_8x8.png"
+                    .into(),
             },
         },
         Font {
             family: "ibm437r",
             font: ibm437r,
             license: License {
-                spdx: "MIT",
-                url: "https://github.com/sbechet/ibm437/blob/master/LICENCE",
+                spdx: "MIT".into(),
+                url: Some("https://github.com/sbechet/ibm437/blob/master/LICENCE".into()),
+                copyright: Some("Copyright (c) Sebastien Bechet".into()),
+            },
+            provenance: Provenance {
+                author: "Sebastien Bechet".into(),
+                url: "https://github.com/sbechet/ibm437".into(),
+                version: None,
+                conversion: "ASCII part of the IBM PC 8x8 regular font, from atlas/ibm437r_8x8.png"
+                    .into(),
             },
         },
         Font {
             family: "mem",
             font: mem44,
-            license: License {
-                spdx: "AGPL-3.0",
-                url: "https://github.com/oidoid/mem/blob/main/license.text",
+            license: MEM_LICENSE.clone(),
+            provenance: Provenance {
+                author: "oidoid".into(),
+                url: "https://github.com/oidoid/mem".into(),
+                version: None,
+                conversion: "mem 4x4 from atlas/mem_4x4.png".into(),
             },
         },
         Font {
             family: "mem",
             font: mem45,
-            license: License {
-                spdx: "AGPL-3.0",
-                url: "https://github.com/oidoid/mem/blob/main/license.text",
+            license: MEM_LICENSE.clone(),
+            provenance: Provenance {
+                author: "oidoid".into(),
+                url: "https://github.com/oidoid/mem".into(),
+                version: None,
+                conversion: "mem 4x5 from atlas/mem_4x5.png".into(),
             },
         },
         Font {
             family: "mem",
             font: mem55,
-            license: License {
-                spdx: "AGPL-3.0",
-                url: "https://github.com/oidoid/mem/blob/main/license.text",
+            license: MEM_LICENSE.clone(),
+            provenance: Provenance {
+                author: "oidoid".into(),
+                url: "https://github.com/oidoid/mem".into(),
+                version: None,
+                conversion: "mem 5x5 from atlas/mem_5x5.png".into(),
             },
         },
         Font {
             family: "sixel",
             font: sixel,
             license: License {
-                spdx: "MIT",
-                url: "https://saitoha.github.io/libsixel/",
+                spdx: "MIT".into(),
+                url: Some("https://github.com/saitoha/libsixel/blob/master/LICENSE".into()),
+                copyright: Some("Copyright (c) Hayaki Saito".into()),
+            },
+            provenance: Provenance {
+                author: "Hayaki Saito".into(),
+                url: "https://github.com/saitoha/libsixel".into(),
+                version: None,
+                conversion: "sixel characters from atlas/sixel_1x6.png".into(),
             },
         },
         Font {
             family: "kenney",
             font: kenney11,
             license: License {
-                spdx: "CC0-1.0",
-                url: "https://www.kenney.nl/assets/1-bit-pack",
+                spdx: "CC0-1.0".into(),
+                url: None,
+                copyright: None,
+            },
+            provenance: Provenance {
+                author: "Kenney".into(),
+                url: "https://www.kenney.nl/assets/1-bit-pack".into(),
+                version: None,
+                conversion: "1-Bit Pack font 11x14 from atlas/kenney_11x14.png".into(),
             },
         },
         Font {
             family: "kenney",
             font: kenney16,
             license: License {
-                spdx: "CC0-1.0",
-                url: "https://www.kenney.nl/assets/1-bit-pack",
+                spdx: "CC0-1.0".into(),
+                url: None,
+                copyright: None,
+            },
+            provenance: Provenance {
+                author: "Kenney".into(),
+                url: "https://www.kenney.nl/assets/1-bit-pack".into(),
+                version: None,
+                conversion: "1-Bit Pack font 16x16 from atlas/kenney_16x16.png".into(),
             },
         },
     ]
 }
 
-/// Load font atlases from filesystem.
-pub(crate) fn load_atlases() -> Result<Atlases> {
+/// Load font atlases from the given directory.
+pub(crate) fn load_atlases(dir: &Path) -> Result<Atlases> {
+    let files = [
+        ("ibm437b", "ibm437b_8x8.png"),
+        ("ibm437r", "ibm437r_8x8.png"),
+        ("pico8", "pico8_4x6.png"),
+        ("profont", "profont_5x9.png"),
+        ("mem44", "mem_4x4.png"),
+        ("mem45", "mem_4x5.png"),
+        ("mem55", "mem_5x5.png"),
+        ("sixel", "sixel_1x6.png"),
+        ("kenney11", "kenney_11x14.png"),
+        ("kenney16", "kenney_16x16.png"),
+    ];
     let mut res = HashMap::new();
-    res.insert("ibm437b", load_atlas("ibm437b_8x8.png")?);
-    res.insert("ibm437r", load_atlas("ibm437r_8x8.png")?);
-    res.insert("pico8", load_atlas("pico8_4x6.png")?);
-    res.insert("profont", load_atlas("profont_5x9.png")?);
-    res.insert("mem44", load_atlas("mem_4x4.png")?);
-    res.insert("mem45", load_atlas("mem_4x5.png")?);
-    res.insert("mem55", load_atlas("mem_5x5.png")?);
-    res.insert("sixel", load_atlas("sixel_1x6.png")?);
-    res.insert("kenney11", load_atlas("kenney_11x14.png")?);
-    res.insert("kenney16", load_atlas("kenney_16x16.png")?);
+    for (name, file_name) in files {
+        let atlas =
+            load_atlas(&dir.join(file_name)).with_context(|| format!("load {file_name}"))?;
+        res.insert(name, atlas);
+    }
     Ok(res)
 }
 
-fn load_atlas(file_name: &'static str) -> Result<Vec<u8>> {
-    let path = PathBuf::new().join("atlas").join(file_name);
+pub(crate) fn load_atlas(path: &Path) -> Result<Atlas> {
     let file = image::ImageReader::open(path).context("open image file")?;
     let img = file.decode().context("decode image")?;
     let img = img.to_luma8();
@@ -230,5 +364,28 @@ fn load_atlas(file_name: &'static str) -> Result<Vec<u8>> {
             byte = 0;
         }
     }
-    Ok(raw)
+    let order_path = path.with_extension("txt");
+    let order = if order_path.exists() {
+        GlyphOrder::read(&order_path)?
+    } else {
+        GlyphOrder::ascii()
+    };
+    Ok(Atlas {
+        data: raw,
+        width: img.width(),
+        order,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eg_version() {
+        let manifest = include_str!("../Cargo.toml");
+        let version = EG_PROVENANCE.version.as_deref().unwrap();
+        let entry = format!("embedded-graphics = {{ version = \"={version}\" }}");
+        assert!(manifest.contains(&entry), "update EG_PROVENANCE.version");
+    }
 }
@@ -0,0 +1,535 @@
+use crate::bitmap::is_visible;
+use crate::generator::{font_to_bytes, glyph_mapping};
+use anyhow::{bail, Context, Result};
+use embedded_graphics::mono_font::mapping::GlyphMapping;
+use embedded_graphics::mono_font::MonoFont;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// The first byte of a font file covering the whole encoding.
+pub(crate) const MAGIC: u8 = 0x11;
+
+/// The first byte of a font file that has a glyph mapping table.
+///
+/// The table goes right after the regular header: one byte with the number
+/// of glyphs followed by, for each glyph in the atlas, the index
+/// the glyph has in the full encoding. The indices are sorted.
+pub(crate) const MAGIC_MAPPED: u8 = 0x12;
+
+/// The first byte of a font file indexed by Unicode code points.
+///
+/// For fonts with more glyphs than fit into an 8-bit encoding. The encoding byte
+/// is [`UNICODE`], and the regular header is followed by a table of code point ranges:
+/// the number of ranges (u16) and, for each range, the first code point (u32)
+/// and the number of code points in it (u16). All numbers are little-endian.
+/// Glyphs in the atlas go in the order of code points, ranges are sorted
+/// and don't overlap.
+pub(crate) const MAGIC_UNICODE: u8 = 0x13;
+
+/// The encoding byte of Unicode-indexed fonts.
+pub(crate) const UNICODE: u8 = 0xFF;
+
+/// The last bytes of a font file that has metadata.
+///
+/// Metadata goes right after the atlas: `key=value` lines in UTF-8, then the size
+/// of the lines in bytes (u16, little-endian), then this tag. Keys can repeat
+/// (one `license` line per license). The atlas of a file with metadata has exactly
+/// the whole glyph rows needed for its glyphs, so the metadata is found by that size
+/// and not only by the tag. Readers that don't know about metadata ignore it
+/// as long as they don't check the file size.
+pub(crate) const METADATA_TAG: &[u8; 4] = b"FFFm";
+
+const HEADER_SIZE: usize = 7;
+
+/// The header stores glyph width, height, and baseline as u8.
+pub(crate) const MAX_GLYPH_SIZE: u32 = u8::MAX as u32;
+/// The header stores the atlas width as u16.
+pub(crate) const MAX_ATLAS_WIDTH: u32 = u16::MAX as u32;
+
+/// Keys and values of the font file metadata, in order.
+pub(crate) type Metadata = Vec<(String, String)>;
+
+/// A font in the Firefly Font Format loaded into memory.
+#[derive(Clone)]
+pub(crate) struct Fff {
+    pub encoding: u8,
+    pub width: u32,
+    pub height: u32,
+    pub baseline: u32,
+    /// The width of the atlas in pixels.
+    pub atlas_width: u32,
+    /// The glyph mapping table. Empty if the atlas covers the whole encoding.
+    pub mapping: Vec<u8>,
+    /// Code points of all glyphs, sorted. Empty if the font uses an 8-bit encoding.
+    pub code_points: Vec<char>,
+    /// 1-bit packed glyph atlas, row by row.
+    pub atlas: Vec<u8>,
+    /// The metadata, like the font author and license.
+    pub metadata: Metadata,
+}
+
+impl Fff {
+    pub fn read(path: &Path) -> Result<Self> {
+        let raw = std::fs::read(path).context("read font file")?;
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &[u8]) -> Result<Self> {
+        if raw.len() < HEADER_SIZE {
+            bail!("font file is too short");
+        }
+        let mut font = Self {
+            encoding: raw[1],
+            width: u32::from(raw[2]),
+            height: u32::from(raw[3]),
+            baseline: u32::from(raw[4]),
+            atlas_width: u32::from(u16::from_le_bytes([raw[5], raw[6]])),
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        let mut atlas = &raw[HEADER_SIZE..];
+        match raw[0] {
+            MAGIC => {}
+            MAGIC_MAPPED => {
+                let Some((&count, rest)) = atlas.split_first() else {
+                    bail!("glyph mapping table is missing");
+                };
+                let count = usize::from(count);
+                if rest.len() < count {
+                    bail!("glyph mapping table is truncated");
+                }
+                font.mapping = rest[..count].to_vec();
+                atlas = &rest[count..];
+            }
+            MAGIC_UNICODE => {
+                let (code_points, rest) = parse_ranges(atlas)?;
+                font.code_points = code_points;
+                atlas = rest;
+            }
+            magic => bail!("unknown magic number: {magic:#x}"),
+        }
+        if font.width == 0 || font.height == 0 || !font.atlas_width.is_multiple_of(8) {
+            bail!("invalid font metrics");
+        }
+        // A zero-width atlas has no columns to look glyphs up in.
+        if font.atlas_width < font.width || !font.atlas_width.is_multiple_of(font.width) {
+            bail!("atlas width is not a multiple of the glyph width");
+        }
+        let (atlas, metadata) = split_metadata(atlas, font.atlas_size())?;
+        font.atlas = atlas.to_vec();
+        font.metadata = metadata;
+        if font.glyph_count() < font.code_points.len() {
+            bail!("atlas has fewer glyphs than code points");
+        }
+        Ok(font)
+    }
+
+    /// Convert an embedded-graphics font into FFF.
+    ///
+    /// Glyphs are looked up by the glyph mapping of the font, so the atlas can have them
+    /// in any order (see [`GlyphOrder`](crate::order::GlyphOrder)). Atlases already
+    /// in the order of the encoding are copied as is, padded to whole glyph rows.
+    pub fn from_mono(encoding_index: usize, font: &MonoFont) -> Self {
+        let mut raw = Self::parse(&font_to_bytes(encoding_index, font)).unwrap();
+        // The image can end in the middle of the last glyph row.
+        if let Some(size) = raw.atlas_size().filter(|&size| size > raw.atlas.len()) {
+            raw.atlas.resize(size, 0);
+        }
+        let mapping = glyph_mapping(encoding_index as u8).unwrap();
+        let glyphs: Vec<usize> = mapping
+            .chars()
+            .map(|c| font.glyph_mapping.index(c))
+            .collect();
+        if glyphs.iter().enumerate().all(|(i, &g)| i == g) {
+            return raw;
+        }
+        let count = raw.glyph_count();
+        Self::from_glyphs(&raw, glyphs.len(), |g, x, y| {
+            glyphs[g] < count && raw.pixel(glyphs[g], x, y)
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut raw = Vec::with_capacity(HEADER_SIZE + 1 + self.mapping.len() + self.atlas.len());
+        let magic = if !self.code_points.is_empty() {
+            MAGIC_UNICODE
+        } else if !self.mapping.is_empty() {
+            MAGIC_MAPPED
+        } else {
+            MAGIC
+        };
+        raw.push(magic);
+        raw.push(self.encoding);
+        raw.push(self.width as u8);
+        raw.push(self.height as u8);
+        raw.push(self.baseline as u8);
+        raw.extend_from_slice(&(self.atlas_width as u16).to_le_bytes());
+        if !self.mapping.is_empty() {
+            raw.push(self.mapping.len() as u8);
+            raw.extend_from_slice(&self.mapping);
+        }
+        if !self.code_points.is_empty() {
+            let ranges = code_point_ranges(&self.code_points);
+            raw.extend_from_slice(&(ranges.len() as u16).to_le_bytes());
+            for (start, len) in ranges {
+                raw.extend_from_slice(&start.to_le_bytes());
+                raw.extend_from_slice(&len.to_le_bytes());
+            }
+        }
+        raw.extend_from_slice(&self.atlas);
+        if !self.metadata.is_empty() {
+            if Some(self.atlas.len()) != self.atlas_size() {
+                bail!("atlas size doesn't match the number of glyphs, can't add metadata");
+            }
+            let mut lines = String::new();
+            for (key, value) in &self.metadata {
+                lines.push_str(&format!("{key}={}\n", value.replace('\n', " ")));
+            }
+            let size = u16::try_from(lines.len()).context("metadata is too long")?;
+            raw.extend_from_slice(lines.as_bytes());
+            raw.extend_from_slice(&size.to_le_bytes());
+            raw.extend_from_slice(METADATA_TAG);
+        }
+        Ok(raw)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_bytes()?).context("write font file")
+    }
+
+    /// How many glyphs fit into a single row of the atlas.
+    pub fn columns(&self) -> u32 {
+        self.atlas_width / self.width
+    }
+
+    /// The number of glyphs in the atlas.
+    pub fn glyph_count(&self) -> usize {
+        let atlas_height = self.atlas.len() as u32 * 8 / self.atlas_width;
+        (self.columns() * (atlas_height / self.height)) as usize
+    }
+
+    /// The size of the atlas in bytes implied by the header and the tables:
+    /// whole glyph rows fitting all glyphs of the encoding, the mapping table,
+    /// or the code point ranges.
+    fn atlas_size(&self) -> Option<usize> {
+        let glyphs = if !self.code_points.is_empty() {
+            self.code_points.len()
+        } else if !self.mapping.is_empty() {
+            self.mapping.len()
+        } else {
+            glyph_mapping(self.encoding)?.chars().count()
+        };
+        if self.columns() == 0 {
+            return None;
+        }
+        let rows = glyphs.div_ceil(self.columns() as usize);
+        Some(rows * (self.height * self.atlas_width / 8) as usize)
+    }
+
+    /// The index of the glyph for the character, if the font has it.
+    ///
+    /// Works the same for all kinds of fonts: full 8-bit encodings,
+    /// their subsets, and Unicode-indexed fonts.
+    pub fn glyph_index(&self, c: char) -> Option<usize> {
+        if !self.code_points.is_empty() {
+            return self.code_points.binary_search(&c).ok();
+        }
+        let mapping = glyph_mapping(self.encoding)?;
+        if !mapping.contains(c) {
+            return None;
+        }
+        let index = mapping.index(c);
+        if self.mapping.is_empty() {
+            return Some(index);
+        }
+        self.mapping.iter().position(|&i| usize::from(i) == index)
+    }
+
+    /// Characters of the font and their glyphs, in the order of glyphs.
+    ///
+    /// Characters without a visible glyph (control characters and placeholders
+    /// for undefined code points) are skipped.
+    pub fn visible_glyphs(&self) -> Vec<(char, usize)> {
+        let chars: Vec<char> = match glyph_mapping(self.encoding) {
+            Some(mapping) => mapping.chars().collect(),
+            None => self.code_points.clone(),
+        };
+        let mut seen = HashSet::new();
+        let mut result: Vec<(char, usize)> = Vec::new();
+        for c in chars {
+            if !is_visible(c) || !seen.insert(c) {
+                continue;
+            }
+            if let Some(glyph) = self.glyph_index(c) {
+                result.push((c, glyph));
+            }
+        }
+        result
+    }
+
+    /// Check if the pixel at the given point of the given glyph is set.
+    pub fn pixel(&self, glyph: usize, x: u32, y: u32) -> bool {
+        let glyph = glyph as u32;
+        let atlas_x = glyph % self.columns() * self.width + x;
+        let atlas_y = glyph / self.columns() * self.height + y;
+        let bit = (atlas_y * self.atlas_width + atlas_x) as usize;
+        match self.atlas.get(bit / 8) {
+            Some(byte) => byte & (0x80 >> (bit % 8)) != 0,
+            None => false,
+        }
+    }
+
+    /// Same as [`Fff::pixel`] but pixels outside of the glyph are never set.
+    pub fn pixel_checked(&self, glyph: usize, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
+        }
+        self.pixel(glyph, x as u32, y as u32)
+    }
+
+    /// Lay out the given number of glyphs into a new atlas.
+    ///
+    /// The callback tells if the pixel at the given point of the given glyph is set.
+    pub fn from_glyphs<F>(template: &Self, count: usize, pixel: F) -> Self
+    where
+        F: Fn(usize, u32, u32) -> bool,
+    {
+        let width = template.width;
+        let height = template.height;
+        let columns = atlas_columns(count, width);
+        let rows = (count as u32).div_ceil(columns);
+        let atlas_width = columns * width;
+        let mut atlas = vec![0u8; (atlas_width * rows * height / 8) as usize];
+        for glyph in 0..count {
+            let glyph_x = glyph as u32 % columns * width;
+            let glyph_y = glyph as u32 / columns * height;
+            for y in 0..height {
+                for x in 0..width {
+                    if pixel(glyph, x, y) {
+                        let bit = ((glyph_y + y) * atlas_width + glyph_x + x) as usize;
+                        atlas[bit / 8] |= 0x80 >> (bit % 8);
+                    }
+                }
+            }
+        }
+        Self {
+            encoding: template.encoding,
+            width,
+            height,
+            baseline: template.baseline,
+            atlas_width,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas,
+            metadata: template.metadata.clone(),
+        }
+    }
+}
+
+/// Cut the metadata off the end of the atlas, if it has any.
+///
+/// The atlas has metadata only if it ends with the tag, the size before the tag
+/// matches the lines, and the bytes before the lines are exactly the expected atlas.
+fn split_metadata(raw: &[u8], atlas_size: Option<usize>) -> Result<(&[u8], Metadata)> {
+    let Some((atlas, trailer)) = atlas_size.and_then(|size| raw.split_at_checked(size)) else {
+        return Ok((raw, Vec::new()));
+    };
+    let Some((lines, size)) = trailer
+        .strip_suffix(METADATA_TAG)
+        .and_then(|rest| rest.split_last_chunk::<2>())
+    else {
+        return Ok((raw, Vec::new()));
+    };
+    if usize::from(u16::from_le_bytes(*size)) != lines.len() {
+        return Ok((raw, Vec::new()));
+    }
+    let lines = std::str::from_utf8(lines).context("metadata is not UTF-8")?;
+    let mut metadata = Vec::new();
+    for line in lines.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("invalid metadata line: {line}");
+        };
+        metadata.push((key.to_string(), value.to_string()));
+    }
+    Ok((atlas, metadata))
+}
+
+/// Split sorted code points into ranges of consecutive ones: the first code point and the length.
+fn code_point_ranges(code_points: &[char]) -> Vec<(u32, u16)> {
+    let mut ranges: Vec<(u32, u16)> = Vec::new();
+    for &c in code_points {
+        let c = u32::from(c);
+        match ranges.last_mut() {
+            Some((start, len)) if *start + u32::from(*len) == c && *len < u16::MAX => *len += 1,
+            _ => ranges.push((c, 1)),
+        }
+    }
+    ranges
+}
+
+/// Parse the code point ranges table, return the code points and the rest of the file.
+fn parse_ranges(raw: &[u8]) -> Result<(Vec<char>, &[u8])> {
+    let Some((count, mut rest)) = raw.split_first_chunk::<2>() else {
+        bail!("code point table is missing");
+    };
+    let mut code_points: Vec<char> = Vec::new();
+    for _ in 0..u16::from_le_bytes(*count) {
+        let Some((range, tail)) = rest.split_first_chunk::<6>() else {
+            bail!("code point table is truncated");
+        };
+        rest = tail;
+        let start = u32::from_le_bytes([range[0], range[1], range[2], range[3]]);
+        let len = u16::from_le_bytes([range[4], range[5]]);
+        let Some(end) = start.checked_add(u32::from(len)) else {
+            bail!("invalid code point range: {start:#x}+{len}");
+        };
+        for code in start..end {
+            let Some(c) = char::from_u32(code) else {
+                bail!("invalid code point: {code:#x}");
+            };
+            if code_points.last().is_some_and(|&last| last >= c) {
+                bail!("code point ranges are not sorted");
+            }
+            code_points.push(c);
+        }
+    }
+    Ok((code_points, rest))
+}
+
+/// How many glyphs to put in a single atlas row.
+///
+/// Atlases are 16 glyphs wide, same as the ASCII table. Smaller atlases
+/// are squeezed but each row must still end on a byte boundary.
+fn atlas_columns(count: usize, width: u32) -> u32 {
+    let mut columns = 16;
+    while columns > 1 && columns as usize >= count * 2 && (columns / 2 * width).is_multiple_of(8) {
+        columns /= 2;
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_font() -> Fff {
+        let template = Fff {
+            encoding: 0,
+            width: 3,
+            height: 2,
+            baseline: 1,
+            atlas_width: 0,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        Fff::from_glyphs(&template, 20, |g, x, y| {
+            (g as u32 + x + y).is_multiple_of(3)
+        })
+    }
+
+    #[test]
+    fn test_from_glyphs() {
+        let font = make_font();
+        assert_eq!(font.atlas_width, 48);
+        assert_eq!(font.glyph_count(), 32);
+        for g in 0..20 {
+            for y in 0..2 {
+                for x in 0..3 {
+                    assert_eq!(font.pixel(g, x, y), (g as u32 + x + y).is_multiple_of(3));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut font = make_font();
+        font.mapping = vec![1, 4, 9];
+        let raw = font.to_bytes().unwrap();
+        assert_eq!(raw[0], MAGIC_MAPPED);
+        let parsed = Fff::parse(&raw).unwrap();
+        assert_eq!(parsed.mapping, font.mapping);
+        assert_eq!(parsed.atlas, font.atlas);
+        assert_eq!(parsed.atlas_width, font.atlas_width);
+    }
+
+    #[test]
+    fn test_invalid_atlas_width() {
+        // 3x2 glyphs in an atlas 0 and 8 pixels wide.
+        assert!(Fff::parse(&[MAGIC, 0, 3, 2, 1, 0, 0, 0xFF]).is_err());
+        assert!(Fff::parse(&[MAGIC, 0, 3, 2, 1, 8, 0, 0xFF]).is_err());
+        assert!(Fff::parse(&[MAGIC, 0, 4, 2, 1, 8, 0, 0xFF]).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_code_points() {
+        // Two 4x2 glyphs and a single code point range.
+        let font = |start: u32, len: u16| {
+            let mut raw = vec![MAGIC_UNICODE, UNICODE, 4, 2, 1, 8, 0, 1, 0];
+            raw.extend_from_slice(&start.to_le_bytes());
+            raw.extend_from_slice(&len.to_le_bytes());
+            raw.extend_from_slice(&[0xFF, 0xFF]);
+            Fff::parse(&raw)
+        };
+        assert!(font(0x41, 2).is_ok());
+        assert!(font(0x41, 3).is_err());
+        assert!(font(u32::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let mut font = make_font();
+        font.mapping = (0..20).collect();
+        font.metadata = vec![
+            ("license".to_string(), "MIT".to_string()),
+            ("license".to_string(), "CC0-1.0".to_string()),
+            ("conversion".to_string(), "cropped, a=b".to_string()),
+        ];
+        let raw = font.to_bytes().unwrap();
+        assert!(raw.ends_with(METADATA_TAG));
+        let parsed = Fff::parse(&raw).unwrap();
+        assert_eq!(parsed.metadata, font.metadata);
+        assert_eq!(parsed.atlas, font.atlas);
+        assert_eq!(parsed.glyph_count(), font.glyph_count());
+
+        font.metadata = vec![("conversion".to_string(), "a".repeat(u16::MAX.into()))];
+        assert!(font.to_bytes().is_err());
+        font.atlas.push(0);
+        font.metadata = vec![("license".to_string(), "MIT".to_string())];
+        assert!(font.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_atlas_ending_with_tag() {
+        // The tag and a matching size at the end of the atlas are still glyphs.
+        let mut font = make_font();
+        font.mapping = (0..20).collect();
+        let len = font.atlas.len();
+        font.atlas[len - 6..].copy_from_slice(b"\0\0FFFm");
+        let parsed = Fff::parse(&font.to_bytes().unwrap()).unwrap();
+        assert!(parsed.metadata.is_empty());
+        assert_eq!(parsed.atlas, font.atlas);
+    }
+
+    #[test]
+    fn test_unicode_roundtrip() {
+        let mut font = make_font();
+        font.encoding = UNICODE;
+        font.code_points = "ABCDEabcxyzАБВ".chars().collect();
+        font.code_points.sort();
+        let raw = font.to_bytes().unwrap();
+        assert_eq!(raw[0], MAGIC_UNICODE);
+        // 4 ranges: A-E, a-c, x-z, А-В
+        assert_eq!(raw[HEADER_SIZE..HEADER_SIZE + 2], [4, 0]);
+        let parsed = Fff::parse(&raw).unwrap();
+        assert_eq!(parsed.code_points, font.code_points);
+        assert_eq!(parsed.atlas, font.atlas);
+        assert_eq!(parsed.glyph_index('a'), Some(5));
+        assert_eq!(parsed.glyph_index('Б'), Some(12));
+        assert_eq!(parsed.glyph_index('?'), None);
+    }
+}
@@ -2,353 +2,360 @@ use embedded_graphics::mono_font::*;
 
 // TODO: replace copy-pasting with macros, maybe?
 
-static ASCII: &[MonoFont] = &[
-    ascii::FONT_4X6,
-    ascii::FONT_5X7,
-    ascii::FONT_5X8,
-    ascii::FONT_6X9,
-    ascii::FONT_6X10,
-    ascii::FONT_6X12,
-    ascii::FONT_6X13,
-    ascii::FONT_6X13_BOLD,
-    ascii::FONT_6X13_ITALIC,
-    ascii::FONT_7X13,
-    ascii::FONT_7X13_BOLD,
-    ascii::FONT_7X13_ITALIC,
-    ascii::FONT_7X14,
-    ascii::FONT_7X14_BOLD,
-    ascii::FONT_8X13,
-    ascii::FONT_8X13_BOLD,
-    ascii::FONT_8X13_ITALIC,
-    ascii::FONT_9X15,
-    ascii::FONT_9X15_BOLD,
-    ascii::FONT_9X18,
-    ascii::FONT_9X18_BOLD,
-    ascii::FONT_10X20,
+/// embedded-graphics fonts of an encoding with their families.
+///
+/// Bold and italic fonts are separate families, so that their files
+/// don't clash with the regular fonts of the same size.
+pub(crate) type EgFonts = [(&'static str, MonoFont<'static>)];
+
+static ASCII: &EgFonts = &[
+    ("eg", ascii::FONT_4X6),
+    ("eg", ascii::FONT_5X7),
+    ("eg", ascii::FONT_5X8),
+    ("eg", ascii::FONT_6X9),
+    ("eg", ascii::FONT_6X10),
+    ("eg", ascii::FONT_6X12),
+    ("eg", ascii::FONT_6X13),
+    ("eg-bold", ascii::FONT_6X13_BOLD),
+    ("eg-italic", ascii::FONT_6X13_ITALIC),
+    ("eg", ascii::FONT_7X13),
+    ("eg-bold", ascii::FONT_7X13_BOLD),
+    ("eg-italic", ascii::FONT_7X13_ITALIC),
+    ("eg", ascii::FONT_7X14),
+    ("eg-bold", ascii::FONT_7X14_BOLD),
+    ("eg", ascii::FONT_8X13),
+    ("eg-bold", ascii::FONT_8X13_BOLD),
+    ("eg-italic", ascii::FONT_8X13_ITALIC),
+    ("eg", ascii::FONT_9X15),
+    ("eg-bold", ascii::FONT_9X15_BOLD),
+    ("eg", ascii::FONT_9X18),
+    ("eg-bold", ascii::FONT_9X18_BOLD),
+    ("eg", ascii::FONT_10X20),
 ];
 
-static ISO_1: &[MonoFont] = &[
-    iso_8859_1::FONT_4X6,
-    iso_8859_1::FONT_5X7,
-    iso_8859_1::FONT_5X8,
-    iso_8859_1::FONT_6X9,
-    iso_8859_1::FONT_6X10,
-    iso_8859_1::FONT_6X12,
-    iso_8859_1::FONT_6X13,
-    iso_8859_1::FONT_6X13_BOLD,
-    iso_8859_1::FONT_6X13_ITALIC,
-    iso_8859_1::FONT_7X13,
-    iso_8859_1::FONT_7X13_BOLD,
-    iso_8859_1::FONT_7X13_ITALIC,
-    iso_8859_1::FONT_7X14,
-    iso_8859_1::FONT_7X14_BOLD,
-    iso_8859_1::FONT_8X13,
-    iso_8859_1::FONT_8X13_BOLD,
-    iso_8859_1::FONT_8X13_ITALIC,
-    iso_8859_1::FONT_9X15,
-    iso_8859_1::FONT_9X15_BOLD,
-    iso_8859_1::FONT_9X18,
-    iso_8859_1::FONT_9X18_BOLD,
-    iso_8859_1::FONT_10X20,
+static ISO_1: &EgFonts = &[
+    ("eg", iso_8859_1::FONT_4X6),
+    ("eg", iso_8859_1::FONT_5X7),
+    ("eg", iso_8859_1::FONT_5X8),
+    ("eg", iso_8859_1::FONT_6X9),
+    ("eg", iso_8859_1::FONT_6X10),
+    ("eg", iso_8859_1::FONT_6X12),
+    ("eg", iso_8859_1::FONT_6X13),
+    ("eg-bold", iso_8859_1::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_1::FONT_6X13_ITALIC),
+    ("eg", iso_8859_1::FONT_7X13),
+    ("eg-bold", iso_8859_1::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_1::FONT_7X13_ITALIC),
+    ("eg", iso_8859_1::FONT_7X14),
+    ("eg-bold", iso_8859_1::FONT_7X14_BOLD),
+    ("eg", iso_8859_1::FONT_8X13),
+    ("eg-bold", iso_8859_1::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_1::FONT_8X13_ITALIC),
+    ("eg", iso_8859_1::FONT_9X15),
+    ("eg-bold", iso_8859_1::FONT_9X15_BOLD),
+    ("eg", iso_8859_1::FONT_9X18),
+    ("eg-bold", iso_8859_1::FONT_9X18_BOLD),
+    ("eg", iso_8859_1::FONT_10X20),
 ];
 
-static ISO_2: &[MonoFont] = &[
-    iso_8859_2::FONT_4X6,
-    iso_8859_2::FONT_5X7,
-    iso_8859_2::FONT_5X8,
-    iso_8859_2::FONT_6X9,
-    iso_8859_2::FONT_6X10,
-    iso_8859_2::FONT_6X12,
-    iso_8859_2::FONT_6X13,
-    iso_8859_2::FONT_6X13_BOLD,
-    iso_8859_2::FONT_6X13_ITALIC,
-    iso_8859_2::FONT_7X13,
-    iso_8859_2::FONT_7X13_BOLD,
-    iso_8859_2::FONT_7X13_ITALIC,
-    iso_8859_2::FONT_7X14,
-    iso_8859_2::FONT_7X14_BOLD,
-    iso_8859_2::FONT_8X13,
-    iso_8859_2::FONT_8X13_BOLD,
-    iso_8859_2::FONT_8X13_ITALIC,
-    iso_8859_2::FONT_9X15,
-    iso_8859_2::FONT_9X15_BOLD,
-    iso_8859_2::FONT_9X18,
-    iso_8859_2::FONT_9X18_BOLD,
-    iso_8859_2::FONT_10X20,
+static ISO_2: &EgFonts = &[
+    ("eg", iso_8859_2::FONT_4X6),
+    ("eg", iso_8859_2::FONT_5X7),
+    ("eg", iso_8859_2::FONT_5X8),
+    ("eg", iso_8859_2::FONT_6X9),
+    ("eg", iso_8859_2::FONT_6X10),
+    ("eg", iso_8859_2::FONT_6X12),
+    ("eg", iso_8859_2::FONT_6X13),
+    ("eg-bold", iso_8859_2::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_2::FONT_6X13_ITALIC),
+    ("eg", iso_8859_2::FONT_7X13),
+    ("eg-bold", iso_8859_2::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_2::FONT_7X13_ITALIC),
+    ("eg", iso_8859_2::FONT_7X14),
+    ("eg-bold", iso_8859_2::FONT_7X14_BOLD),
+    ("eg", iso_8859_2::FONT_8X13),
+    ("eg-bold", iso_8859_2::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_2::FONT_8X13_ITALIC),
+    ("eg", iso_8859_2::FONT_9X15),
+    ("eg-bold", iso_8859_2::FONT_9X15_BOLD),
+    ("eg", iso_8859_2::FONT_9X18),
+    ("eg-bold", iso_8859_2::FONT_9X18_BOLD),
+    ("eg", iso_8859_2::FONT_10X20),
 ];
 
-static ISO_3: &[MonoFont] = &[
-    iso_8859_3::FONT_4X6,
-    iso_8859_3::FONT_5X7,
-    iso_8859_3::FONT_5X8,
-    iso_8859_3::FONT_6X9,
-    iso_8859_3::FONT_6X10,
-    iso_8859_3::FONT_6X12,
-    iso_8859_3::FONT_6X13,
-    iso_8859_3::FONT_6X13_BOLD,
-    iso_8859_3::FONT_6X13_ITALIC,
-    iso_8859_3::FONT_7X13,
-    iso_8859_3::FONT_7X13_BOLD,
-    iso_8859_3::FONT_7X13_ITALIC,
-    iso_8859_3::FONT_7X14,
-    iso_8859_3::FONT_7X14_BOLD,
-    iso_8859_3::FONT_8X13,
-    iso_8859_3::FONT_8X13_BOLD,
-    iso_8859_3::FONT_8X13_ITALIC,
-    iso_8859_3::FONT_9X15,
-    iso_8859_3::FONT_9X15_BOLD,
-    iso_8859_3::FONT_9X18,
-    iso_8859_3::FONT_9X18_BOLD,
-    iso_8859_3::FONT_10X20,
+static ISO_3: &EgFonts = &[
+    ("eg", iso_8859_3::FONT_4X6),
+    ("eg", iso_8859_3::FONT_5X7),
+    ("eg", iso_8859_3::FONT_5X8),
+    ("eg", iso_8859_3::FONT_6X9),
+    ("eg", iso_8859_3::FONT_6X10),
+    ("eg", iso_8859_3::FONT_6X12),
+    ("eg", iso_8859_3::FONT_6X13),
+    ("eg-bold", iso_8859_3::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_3::FONT_6X13_ITALIC),
+    ("eg", iso_8859_3::FONT_7X13),
+    ("eg-bold", iso_8859_3::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_3::FONT_7X13_ITALIC),
+    ("eg", iso_8859_3::FONT_7X14),
+    ("eg-bold", iso_8859_3::FONT_7X14_BOLD),
+    ("eg", iso_8859_3::FONT_8X13),
+    ("eg-bold", iso_8859_3::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_3::FONT_8X13_ITALIC),
+    ("eg", iso_8859_3::FONT_9X15),
+    ("eg-bold", iso_8859_3::FONT_9X15_BOLD),
+    ("eg", iso_8859_3::FONT_9X18),
+    ("eg-bold", iso_8859_3::FONT_9X18_BOLD),
+    ("eg", iso_8859_3::FONT_10X20),
 ];
 
-static ISO_4: &[MonoFont] = &[
-    iso_8859_4::FONT_4X6,
-    iso_8859_4::FONT_5X7,
-    iso_8859_4::FONT_5X8,
-    iso_8859_4::FONT_6X9,
-    iso_8859_4::FONT_6X10,
-    iso_8859_4::FONT_6X12,
-    iso_8859_4::FONT_6X13,
-    iso_8859_4::FONT_6X13_BOLD,
-    iso_8859_4::FONT_6X13_ITALIC,
-    iso_8859_4::FONT_7X13,
-    iso_8859_4::FONT_7X13_BOLD,
-    iso_8859_4::FONT_7X13_ITALIC,
-    iso_8859_4::FONT_7X14,
-    iso_8859_4::FONT_7X14_BOLD,
-    iso_8859_4::FONT_8X13,
-    iso_8859_4::FONT_8X13_BOLD,
-    iso_8859_4::FONT_8X13_ITALIC,
-    iso_8859_4::FONT_9X15,
-    iso_8859_4::FONT_9X15_BOLD,
-    iso_8859_4::FONT_9X18,
-    iso_8859_4::FONT_9X18_BOLD,
-    iso_8859_4::FONT_10X20,
+static ISO_4: &EgFonts = &[
+    ("eg", iso_8859_4::FONT_4X6),
+    ("eg", iso_8859_4::FONT_5X7),
+    ("eg", iso_8859_4::FONT_5X8),
+    ("eg", iso_8859_4::FONT_6X9),
+    ("eg", iso_8859_4::FONT_6X10),
+    ("eg", iso_8859_4::FONT_6X12),
+    ("eg", iso_8859_4::FONT_6X13),
+    ("eg-bold", iso_8859_4::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_4::FONT_6X13_ITALIC),
+    ("eg", iso_8859_4::FONT_7X13),
+    ("eg-bold", iso_8859_4::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_4::FONT_7X13_ITALIC),
+    ("eg", iso_8859_4::FONT_7X14),
+    ("eg-bold", iso_8859_4::FONT_7X14_BOLD),
+    ("eg", iso_8859_4::FONT_8X13),
+    ("eg-bold", iso_8859_4::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_4::FONT_8X13_ITALIC),
+    ("eg", iso_8859_4::FONT_9X15),
+    ("eg-bold", iso_8859_4::FONT_9X15_BOLD),
+    ("eg", iso_8859_4::FONT_9X18),
+    ("eg-bold", iso_8859_4::FONT_9X18_BOLD),
+    ("eg", iso_8859_4::FONT_10X20),
 ];
 
-static ISO_5: &[MonoFont] = &[
-    iso_8859_5::FONT_4X6,
-    iso_8859_5::FONT_5X7,
-    iso_8859_5::FONT_5X8,
-    iso_8859_5::FONT_6X9,
-    iso_8859_5::FONT_6X10,
-    iso_8859_5::FONT_6X12,
-    iso_8859_5::FONT_6X13,
-    iso_8859_5::FONT_6X13_BOLD,
-    iso_8859_5::FONT_6X13_ITALIC,
-    iso_8859_5::FONT_7X13,
-    iso_8859_5::FONT_7X13_BOLD,
-    iso_8859_5::FONT_7X13_ITALIC,
-    iso_8859_5::FONT_7X14,
-    iso_8859_5::FONT_7X14_BOLD,
-    iso_8859_5::FONT_8X13,
-    iso_8859_5::FONT_8X13_BOLD,
-    iso_8859_5::FONT_8X13_ITALIC,
-    iso_8859_5::FONT_9X15,
-    iso_8859_5::FONT_9X15_BOLD,
-    iso_8859_5::FONT_9X18,
-    iso_8859_5::FONT_9X18_BOLD,
-    iso_8859_5::FONT_10X20,
+static ISO_5: &EgFonts = &[
+    ("eg", iso_8859_5::FONT_4X6),
+    ("eg", iso_8859_5::FONT_5X7),
+    ("eg", iso_8859_5::FONT_5X8),
+    ("eg", iso_8859_5::FONT_6X9),
+    ("eg", iso_8859_5::FONT_6X10),
+    ("eg", iso_8859_5::FONT_6X12),
+    ("eg", iso_8859_5::FONT_6X13),
+    ("eg-bold", iso_8859_5::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_5::FONT_6X13_ITALIC),
+    ("eg", iso_8859_5::FONT_7X13),
+    ("eg-bold", iso_8859_5::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_5::FONT_7X13_ITALIC),
+    ("eg", iso_8859_5::FONT_7X14),
+    ("eg-bold", iso_8859_5::FONT_7X14_BOLD),
+    ("eg", iso_8859_5::FONT_8X13),
+    ("eg-bold", iso_8859_5::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_5::FONT_8X13_ITALIC),
+    ("eg", iso_8859_5::FONT_9X15),
+    ("eg-bold", iso_8859_5::FONT_9X15_BOLD),
+    ("eg", iso_8859_5::FONT_9X18),
+    ("eg-bold", iso_8859_5::FONT_9X18_BOLD),
+    ("eg", iso_8859_5::FONT_10X20),
 ];
 
-static ISO_7: &[MonoFont] = &[
-    iso_8859_7::FONT_4X6,
-    iso_8859_7::FONT_5X7,
-    iso_8859_7::FONT_5X8,
-    iso_8859_7::FONT_6X9,
-    iso_8859_7::FONT_6X10,
-    iso_8859_7::FONT_6X12,
-    iso_8859_7::FONT_6X13,
-    iso_8859_7::FONT_6X13_BOLD,
-    iso_8859_7::FONT_6X13_ITALIC,
-    iso_8859_7::FONT_7X13,
-    iso_8859_7::FONT_7X13_BOLD,
-    iso_8859_7::FONT_7X13_ITALIC,
-    iso_8859_7::FONT_7X14,
-    iso_8859_7::FONT_7X14_BOLD,
-    iso_8859_7::FONT_8X13,
-    iso_8859_7::FONT_8X13_BOLD,
-    iso_8859_7::FONT_8X13_ITALIC,
-    iso_8859_7::FONT_9X15,
-    iso_8859_7::FONT_9X15_BOLD,
-    iso_8859_7::FONT_9X18,
-    iso_8859_7::FONT_9X18_BOLD,
-    iso_8859_7::FONT_10X20,
+static ISO_7: &EgFonts = &[
+    ("eg", iso_8859_7::FONT_4X6),
+    ("eg", iso_8859_7::FONT_5X7),
+    ("eg", iso_8859_7::FONT_5X8),
+    ("eg", iso_8859_7::FONT_6X9),
+    ("eg", iso_8859_7::FONT_6X10),
+    ("eg", iso_8859_7::FONT_6X12),
+    ("eg", iso_8859_7::FONT_6X13),
+    ("eg-bold", iso_8859_7::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_7::FONT_6X13_ITALIC),
+    ("eg", iso_8859_7::FONT_7X13),
+    ("eg-bold", iso_8859_7::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_7::FONT_7X13_ITALIC),
+    ("eg", iso_8859_7::FONT_7X14),
+    ("eg-bold", iso_8859_7::FONT_7X14_BOLD),
+    ("eg", iso_8859_7::FONT_8X13),
+    ("eg-bold", iso_8859_7::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_7::FONT_8X13_ITALIC),
+    ("eg", iso_8859_7::FONT_9X15),
+    ("eg-bold", iso_8859_7::FONT_9X15_BOLD),
+    ("eg", iso_8859_7::FONT_9X18),
+    ("eg-bold", iso_8859_7::FONT_9X18_BOLD),
+    ("eg", iso_8859_7::FONT_10X20),
 ];
 
-static ISO_9: &[MonoFont] = &[
-    iso_8859_9::FONT_4X6,
-    iso_8859_9::FONT_5X7,
-    iso_8859_9::FONT_5X8,
-    iso_8859_9::FONT_6X9,
-    iso_8859_9::FONT_6X10,
-    iso_8859_9::FONT_6X12,
-    iso_8859_9::FONT_6X13,
-    iso_8859_9::FONT_6X13_BOLD,
-    iso_8859_9::FONT_6X13_ITALIC,
-    iso_8859_9::FONT_7X13,
-    iso_8859_9::FONT_7X13_BOLD,
-    iso_8859_9::FONT_7X13_ITALIC,
-    iso_8859_9::FONT_7X14,
-    iso_8859_9::FONT_7X14_BOLD,
-    iso_8859_9::FONT_8X13,
-    iso_8859_9::FONT_8X13_BOLD,
-    iso_8859_9::FONT_8X13_ITALIC,
-    iso_8859_9::FONT_9X15,
-    iso_8859_9::FONT_9X15_BOLD,
-    iso_8859_9::FONT_9X18,
-    iso_8859_9::FONT_9X18_BOLD,
-    iso_8859_9::FONT_10X20,
+static ISO_9: &EgFonts = &[
+    ("eg", iso_8859_9::FONT_4X6),
+    ("eg", iso_8859_9::FONT_5X7),
+    ("eg", iso_8859_9::FONT_5X8),
+    ("eg", iso_8859_9::FONT_6X9),
+    ("eg", iso_8859_9::FONT_6X10),
+    ("eg", iso_8859_9::FONT_6X12),
+    ("eg", iso_8859_9::FONT_6X13),
+    ("eg-bold", iso_8859_9::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_9::FONT_6X13_ITALIC),
+    ("eg", iso_8859_9::FONT_7X13),
+    ("eg-bold", iso_8859_9::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_9::FONT_7X13_ITALIC),
+    ("eg", iso_8859_9::FONT_7X14),
+    ("eg-bold", iso_8859_9::FONT_7X14_BOLD),
+    ("eg", iso_8859_9::FONT_8X13),
+    ("eg-bold", iso_8859_9::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_9::FONT_8X13_ITALIC),
+    ("eg", iso_8859_9::FONT_9X15),
+    ("eg-bold", iso_8859_9::FONT_9X15_BOLD),
+    ("eg", iso_8859_9::FONT_9X18),
+    ("eg-bold", iso_8859_9::FONT_9X18_BOLD),
+    ("eg", iso_8859_9::FONT_10X20),
 ];
 
-static ISO_10: &[MonoFont] = &[
-    iso_8859_10::FONT_4X6,
-    iso_8859_10::FONT_5X7,
-    iso_8859_10::FONT_5X8,
-    iso_8859_10::FONT_6X9,
-    iso_8859_10::FONT_6X10,
-    iso_8859_10::FONT_6X12,
-    iso_8859_10::FONT_6X13,
-    iso_8859_10::FONT_6X13_BOLD,
-    iso_8859_10::FONT_6X13_ITALIC,
-    iso_8859_10::FONT_7X13,
-    iso_8859_10::FONT_7X13_BOLD,
-    iso_8859_10::FONT_7X13_ITALIC,
-    iso_8859_10::FONT_7X14,
-    iso_8859_10::FONT_7X14_BOLD,
-    iso_8859_10::FONT_8X13,
-    iso_8859_10::FONT_8X13_BOLD,
-    iso_8859_10::FONT_8X13_ITALIC,
-    iso_8859_10::FONT_9X15,
-    iso_8859_10::FONT_9X15_BOLD,
-    iso_8859_10::FONT_9X18,
-    iso_8859_10::FONT_9X18_BOLD,
-    iso_8859_10::FONT_10X20,
+static ISO_10: &EgFonts = &[
+    ("eg", iso_8859_10::FONT_4X6),
+    ("eg", iso_8859_10::FONT_5X7),
+    ("eg", iso_8859_10::FONT_5X8),
+    ("eg", iso_8859_10::FONT_6X9),
+    ("eg", iso_8859_10::FONT_6X10),
+    ("eg", iso_8859_10::FONT_6X12),
+    ("eg", iso_8859_10::FONT_6X13),
+    ("eg-bold", iso_8859_10::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_10::FONT_6X13_ITALIC),
+    ("eg", iso_8859_10::FONT_7X13),
+    ("eg-bold", iso_8859_10::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_10::FONT_7X13_ITALIC),
+    ("eg", iso_8859_10::FONT_7X14),
+    ("eg-bold", iso_8859_10::FONT_7X14_BOLD),
+    ("eg", iso_8859_10::FONT_8X13),
+    ("eg-bold", iso_8859_10::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_10::FONT_8X13_ITALIC),
+    ("eg", iso_8859_10::FONT_9X15),
+    ("eg-bold", iso_8859_10::FONT_9X15_BOLD),
+    ("eg", iso_8859_10::FONT_9X18),
+    ("eg-bold", iso_8859_10::FONT_9X18_BOLD),
+    ("eg", iso_8859_10::FONT_10X20),
 ];
 
-static ISO_13: &[MonoFont] = &[
-    iso_8859_13::FONT_4X6,
-    iso_8859_13::FONT_5X7,
-    iso_8859_13::FONT_5X8,
-    iso_8859_13::FONT_6X9,
-    iso_8859_13::FONT_6X10,
-    iso_8859_13::FONT_6X12,
-    iso_8859_13::FONT_6X13,
-    iso_8859_13::FONT_6X13_BOLD,
-    iso_8859_13::FONT_6X13_ITALIC,
-    iso_8859_13::FONT_7X13,
-    iso_8859_13::FONT_7X13_BOLD,
-    iso_8859_13::FONT_7X13_ITALIC,
-    iso_8859_13::FONT_7X14,
-    iso_8859_13::FONT_7X14_BOLD,
-    iso_8859_13::FONT_8X13,
-    iso_8859_13::FONT_8X13_BOLD,
-    iso_8859_13::FONT_8X13_ITALIC,
-    iso_8859_13::FONT_9X15,
-    iso_8859_13::FONT_9X15_BOLD,
-    iso_8859_13::FONT_9X18,
-    iso_8859_13::FONT_9X18_BOLD,
-    iso_8859_13::FONT_10X20,
+static ISO_13: &EgFonts = &[
+    ("eg", iso_8859_13::FONT_4X6),
+    ("eg", iso_8859_13::FONT_5X7),
+    ("eg", iso_8859_13::FONT_5X8),
+    ("eg", iso_8859_13::FONT_6X9),
+    ("eg", iso_8859_13::FONT_6X10),
+    ("eg", iso_8859_13::FONT_6X12),
+    ("eg", iso_8859_13::FONT_6X13),
+    ("eg-bold", iso_8859_13::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_13::FONT_6X13_ITALIC),
+    ("eg", iso_8859_13::FONT_7X13),
+    ("eg-bold", iso_8859_13::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_13::FONT_7X13_ITALIC),
+    ("eg", iso_8859_13::FONT_7X14),
+    ("eg-bold", iso_8859_13::FONT_7X14_BOLD),
+    ("eg", iso_8859_13::FONT_8X13),
+    ("eg-bold", iso_8859_13::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_13::FONT_8X13_ITALIC),
+    ("eg", iso_8859_13::FONT_9X15),
+    ("eg-bold", iso_8859_13::FONT_9X15_BOLD),
+    ("eg", iso_8859_13::FONT_9X18),
+    ("eg-bold", iso_8859_13::FONT_9X18_BOLD),
+    ("eg", iso_8859_13::FONT_10X20),
 ];
 
-static ISO_14: &[MonoFont] = &[
-    iso_8859_14::FONT_4X6,
-    iso_8859_14::FONT_5X7,
-    iso_8859_14::FONT_5X8,
-    iso_8859_14::FONT_6X9,
-    iso_8859_14::FONT_6X10,
-    iso_8859_14::FONT_6X12,
-    iso_8859_14::FONT_6X13,
-    iso_8859_14::FONT_6X13_BOLD,
-    iso_8859_14::FONT_6X13_ITALIC,
-    iso_8859_14::FONT_7X13,
-    iso_8859_14::FONT_7X13_BOLD,
-    iso_8859_14::FONT_7X13_ITALIC,
-    iso_8859_14::FONT_7X14,
-    iso_8859_14::FONT_7X14_BOLD,
-    iso_8859_14::FONT_8X13,
-    iso_8859_14::FONT_8X13_BOLD,
-    iso_8859_14::FONT_8X13_ITALIC,
-    iso_8859_14::FONT_9X15,
-    iso_8859_14::FONT_9X15_BOLD,
-    iso_8859_14::FONT_9X18,
-    iso_8859_14::FONT_9X18_BOLD,
-    iso_8859_14::FONT_10X20,
+static ISO_14: &EgFonts = &[
+    ("eg", iso_8859_14::FONT_4X6),
+    ("eg", iso_8859_14::FONT_5X7),
+    ("eg", iso_8859_14::FONT_5X8),
+    ("eg", iso_8859_14::FONT_6X9),
+    ("eg", iso_8859_14::FONT_6X10),
+    ("eg", iso_8859_14::FONT_6X12),
+    ("eg", iso_8859_14::FONT_6X13),
+    ("eg-bold", iso_8859_14::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_14::FONT_6X13_ITALIC),
+    ("eg", iso_8859_14::FONT_7X13),
+    ("eg-bold", iso_8859_14::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_14::FONT_7X13_ITALIC),
+    ("eg", iso_8859_14::FONT_7X14),
+    ("eg-bold", iso_8859_14::FONT_7X14_BOLD),
+    ("eg", iso_8859_14::FONT_8X13),
+    ("eg-bold", iso_8859_14::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_14::FONT_8X13_ITALIC),
+    ("eg", iso_8859_14::FONT_9X15),
+    ("eg-bold", iso_8859_14::FONT_9X15_BOLD),
+    ("eg", iso_8859_14::FONT_9X18),
+    ("eg-bold", iso_8859_14::FONT_9X18_BOLD),
+    ("eg", iso_8859_14::FONT_10X20),
 ];
 
-static ISO_15: &[MonoFont] = &[
-    iso_8859_15::FONT_4X6,
-    iso_8859_15::FONT_5X7,
-    iso_8859_15::FONT_5X8,
-    iso_8859_15::FONT_6X9,
-    iso_8859_15::FONT_6X10,
-    iso_8859_15::FONT_6X12,
-    iso_8859_15::FONT_6X13,
-    iso_8859_15::FONT_6X13_BOLD,
-    iso_8859_15::FONT_6X13_ITALIC,
-    iso_8859_15::FONT_7X13,
-    iso_8859_15::FONT_7X13_BOLD,
-    iso_8859_15::FONT_7X13_ITALIC,
-    iso_8859_15::FONT_7X14,
-    iso_8859_15::FONT_7X14_BOLD,
-    iso_8859_15::FONT_8X13,
-    iso_8859_15::FONT_8X13_BOLD,
-    iso_8859_15::FONT_8X13_ITALIC,
-    iso_8859_15::FONT_9X15,
-    iso_8859_15::FONT_9X15_BOLD,
-    iso_8859_15::FONT_9X18,
-    iso_8859_15::FONT_9X18_BOLD,
-    iso_8859_15::FONT_10X20,
+static ISO_15: &EgFonts = &[
+    ("eg", iso_8859_15::FONT_4X6),
+    ("eg", iso_8859_15::FONT_5X7),
+    ("eg", iso_8859_15::FONT_5X8),
+    ("eg", iso_8859_15::FONT_6X9),
+    ("eg", iso_8859_15::FONT_6X10),
+    ("eg", iso_8859_15::FONT_6X12),
+    ("eg", iso_8859_15::FONT_6X13),
+    ("eg-bold", iso_8859_15::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_15::FONT_6X13_ITALIC),
+    ("eg", iso_8859_15::FONT_7X13),
+    ("eg-bold", iso_8859_15::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_15::FONT_7X13_ITALIC),
+    ("eg", iso_8859_15::FONT_7X14),
+    ("eg-bold", iso_8859_15::FONT_7X14_BOLD),
+    ("eg", iso_8859_15::FONT_8X13),
+    ("eg-bold", iso_8859_15::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_15::FONT_8X13_ITALIC),
+    ("eg", iso_8859_15::FONT_9X15),
+    ("eg-bold", iso_8859_15::FONT_9X15_BOLD),
+    ("eg", iso_8859_15::FONT_9X18),
+    ("eg-bold", iso_8859_15::FONT_9X18_BOLD),
+    ("eg", iso_8859_15::FONT_10X20),
 ];
 
-static ISO_16: &[MonoFont] = &[
-    iso_8859_16::FONT_4X6,
-    iso_8859_16::FONT_5X7,
-    iso_8859_16::FONT_5X8,
-    iso_8859_16::FONT_6X9,
-    iso_8859_16::FONT_6X10,
-    iso_8859_16::FONT_6X12,
-    iso_8859_16::FONT_6X13,
-    iso_8859_16::FONT_6X13_BOLD,
-    iso_8859_16::FONT_6X13_ITALIC,
-    iso_8859_16::FONT_7X13,
-    iso_8859_16::FONT_7X13_BOLD,
-    iso_8859_16::FONT_7X13_ITALIC,
-    iso_8859_16::FONT_7X14,
-    iso_8859_16::FONT_7X14_BOLD,
-    iso_8859_16::FONT_8X13,
-    iso_8859_16::FONT_8X13_BOLD,
-    iso_8859_16::FONT_8X13_ITALIC,
-    iso_8859_16::FONT_9X15,
-    iso_8859_16::FONT_9X15_BOLD,
-    iso_8859_16::FONT_9X18,
-    iso_8859_16::FONT_9X18_BOLD,
-    iso_8859_16::FONT_10X20,
+static ISO_16: &EgFonts = &[
+    ("eg", iso_8859_16::FONT_4X6),
+    ("eg", iso_8859_16::FONT_5X7),
+    ("eg", iso_8859_16::FONT_5X8),
+    ("eg", iso_8859_16::FONT_6X9),
+    ("eg", iso_8859_16::FONT_6X10),
+    ("eg", iso_8859_16::FONT_6X12),
+    ("eg", iso_8859_16::FONT_6X13),
+    ("eg-bold", iso_8859_16::FONT_6X13_BOLD),
+    ("eg-italic", iso_8859_16::FONT_6X13_ITALIC),
+    ("eg", iso_8859_16::FONT_7X13),
+    ("eg-bold", iso_8859_16::FONT_7X13_BOLD),
+    ("eg-italic", iso_8859_16::FONT_7X13_ITALIC),
+    ("eg", iso_8859_16::FONT_7X14),
+    ("eg-bold", iso_8859_16::FONT_7X14_BOLD),
+    ("eg", iso_8859_16::FONT_8X13),
+    ("eg-bold", iso_8859_16::FONT_8X13_BOLD),
+    ("eg-italic", iso_8859_16::FONT_8X13_ITALIC),
+    ("eg", iso_8859_16::FONT_9X15),
+    ("eg-bold", iso_8859_16::FONT_9X15_BOLD),
+    ("eg", iso_8859_16::FONT_9X18),
+    ("eg-bold", iso_8859_16::FONT_9X18_BOLD),
+    ("eg", iso_8859_16::FONT_10X20),
 ];
 
-static JISX: &[MonoFont] = &[
-    jis_x0201::FONT_6X13,
-    jis_x0201::FONT_7X14,
-    jis_x0201::FONT_8X13,
-    jis_x0201::FONT_9X15,
-    jis_x0201::FONT_9X18,
-    jis_x0201::FONT_10X20,
+static JISX: &EgFonts = &[
+    ("eg", jis_x0201::FONT_6X13),
+    ("eg", jis_x0201::FONT_7X14),
+    ("eg", jis_x0201::FONT_8X13),
+    ("eg", jis_x0201::FONT_9X15),
+    ("eg", jis_x0201::FONT_9X18),
+    ("eg", jis_x0201::FONT_10X20),
 ];
 
-pub(crate) static FONTS: &[(&str, &str, &[MonoFont])] = &[
-    ("eg", "ascii", ASCII),        // 0. ASCII
-    ("eg", "iso_8859_1", ISO_1),   // 1. Latin-1, Western European.
-    ("eg", "iso_8859_2", ISO_2),   // 2. Latin-2, Central European.
-    ("eg", "iso_8859_3", ISO_3),   // 3. Latin-3, South European.
-    ("eg", "iso_8859_4", ISO_4),   // 4. Latin-4, North European.
-    ("eg", "iso_8859_9", ISO_9),   // 5. Latin-5, Turkish.
-    ("eg", "iso_8859_10", ISO_10), // 6. Latin-6, Nordic.
-    ("eg", "iso_8859_13", ISO_13), // 7. Latin-7, Baltic Rim.
-    ("eg", "iso_8859_14", ISO_14), // 8. Latin-8, Celtic.
-    ("eg", "iso_8859_15", ISO_15), // 9. Latin-9 (revised Latin-1).
-    ("eg", "iso_8859_16", ISO_16), // A. Latin-10: South-East European.
-    ("eg", "iso_8859_5", ISO_5),   // B. Latin/Cyrillic.
-    ("eg", "iso_8859_7", ISO_7),   // C. Latin/Greek.
-    ("eg", "jis_x0201", JISX),     // D. Japanese katakana (halfwidth).
+/// Fonts for each encoding.
+pub(crate) static FONTS: &[(&str, &EgFonts)] = &[
+    ("ascii", ASCII),        // 0. ASCII
+    ("iso_8859_1", ISO_1),   // 1. Latin-1, Western European.
+    ("iso_8859_2", ISO_2),   // 2. Latin-2, Central European.
+    ("iso_8859_3", ISO_3),   // 3. Latin-3, South European.
+    ("iso_8859_4", ISO_4),   // 4. Latin-4, North European.
+    ("iso_8859_9", ISO_9),   // 5. Latin-5, Turkish.
+    ("iso_8859_10", ISO_10), // 6. Latin-6, Nordic.
+    ("iso_8859_13", ISO_13), // 7. Latin-7, Baltic Rim.
+    ("iso_8859_14", ISO_14), // 8. Latin-8, Celtic.
+    ("iso_8859_15", ISO_15), // 9. Latin-9 (revised Latin-1).
+    ("iso_8859_16", ISO_16), // A. Latin-10: South-East European.
+    ("iso_8859_5", ISO_5),   // B. Latin/Cyrillic.
+    ("iso_8859_7", ISO_7),   // C. Latin/Greek.
+    ("jis_x0201", JISX),     // D. Japanese katakana (halfwidth).
 ];
@@ -1,69 +1,92 @@
-use crate::extra_fonts::{get_fonts, load_atlases};
-use crate::fonts::FONTS;
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font};
+use crate::cp437;
+use crate::mappings;
 use anyhow::{Context, Result};
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::OriginDimensions;
+use embedded_graphics::mono_font::mapping::{self, StrGlyphMapping};
 use embedded_graphics::mono_font::MonoFont;
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
-use std::fs::File;
+use rayon::prelude::*;
+use std::collections::HashSet;
 use std::io::{self, Write};
 use std::path::Path;
-
-static ENCODINGS: &[&str] = &[
-    "ascii",       // 0. ASCII
-    "iso_8859_1",  // 1. Latin-1, Western European.
-    "iso_8859_2",  // 2. Latin-2, Central European.
-    "iso_8859_3",  // 3. Latin-3, South European.
-    "iso_8859_4",  // 4. Latin-4, North European.
-    "iso_8859_9",  // 5. Latin-5, Turkish.
-    "iso_8859_10", // 6. Latin-6, Nordic.
-    "iso_8859_13", // 7. Latin-7, Baltic Rim.
-    "iso_8859_14", // 8. Latin-8, Celtic.
-    "iso_8859_15", // 9. Latin-9 (revised Latin-1).
-    "iso_8859_16", // A. Latin-10: South-East European.
-    "iso_8859_5",  // B. Latin/Cyrillic.
-    "iso_8859_7",  // C. Latin/Greek.
-    "jis_x0201",   // D. Japanese katakana (halfwidth).
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ENCODINGS: &[(&str, &StrGlyphMapping)] = &[
+    ("ascii", &mapping::ASCII),              // 0. ASCII
+    ("iso_8859_1", &mapping::ISO_8859_1),    // 1. Latin-1, Western European.
+    ("iso_8859_2", &mapping::ISO_8859_2),    // 2. Latin-2, Central European.
+    ("iso_8859_3", &mapping::ISO_8859_3),    // 3. Latin-3, South European.
+    ("iso_8859_4", &mapping::ISO_8859_4),    // 4. Latin-4, North European.
+    ("iso_8859_9", &mapping::ISO_8859_9),    // 5. Latin-5, Turkish.
+    ("iso_8859_10", &mapping::ISO_8859_10),  // 6. Latin-6, Nordic.
+    ("iso_8859_13", &mapping::ISO_8859_13),  // 7. Latin-7, Baltic Rim.
+    ("iso_8859_14", &mapping::ISO_8859_14),  // 8. Latin-8, Celtic.
+    ("iso_8859_15", &mapping::ISO_8859_15),  // 9. Latin-9 (revised Latin-1).
+    ("iso_8859_16", &mapping::ISO_8859_16),  // A. Latin-10: South-East European.
+    ("iso_8859_5", &mapping::ISO_8859_5),    // B. Latin/Cyrillic.
+    ("iso_8859_7", &mapping::ISO_8859_7),    // C. Latin/Greek.
+    ("jis_x0201", &mapping::JIS_X0201),      // D. Japanese katakana (halfwidth).
+    ("cp437", &cp437::CP437),                // E. Code page 437 (DOS).
+    ("koi8_r", &mappings::KOI8_R),           // F. Russian (KOI8-R).
+    ("koi8_u", &mappings::KOI8_U),           // 10. Ukrainian (KOI8-U).
+    ("cp1251", &mappings::CP1251),           // 11. Windows Cyrillic.
+    ("iso_8859_8", &mappings::ISO_8859_8),   // 12. Latin/Hebrew.
+    ("iso_8859_6", &mappings::ISO_8859_6),   // 13. Latin/Arabic.
+    ("iso_8859_11", &mappings::ISO_8859_11), // 14. Latin/Thai (TIS-620).
 ];
 
-pub(crate) fn save_all_fonts(root: &Path) -> Result<usize> {
-    let mut count = 0;
-    for (family_name, encoding_name, fonts) in FONTS.iter() {
-        let (encoding_index, _) = ENCODINGS
-            .iter()
-            .enumerate()
-            .find(|(_, e)| *e == encoding_name)
-            .unwrap();
-        let dir_path = root.join(encoding_name);
-        std::fs::create_dir_all(&dir_path).context("create encoding dir")?;
-        for font in fonts.iter() {
-            let size = &font.character_size;
-            let file_name = format!("{family_name}_{}x{}.fff", size.width, size.height);
-            let path = dir_path.join(file_name);
-            dump_font(&path, encoding_index, font).context("dump font")?;
-            count += 1
-        }
+/// Write all catalog fonts into per-encoding directories.
+///
+/// Returns how many font files were updated.
+pub(crate) fn save_all_fonts(root: &Path, fonts: &[Font], cache: &BuildCache) -> Result<usize> {
+    let encodings: HashSet<&str> = fonts.iter().map(|f| f.encoding).collect();
+    for encoding_name in encodings {
+        std::fs::create_dir_all(root.join(encoding_name)).context("create encoding dir")?;
     }
+    let updated = AtomicUsize::new(0);
+    unique_files(fonts)
+        .par_iter()
+        .try_for_each(|font| -> Result<()> {
+            let path = root
+                .join(font.encoding)
+                .join(format!("{}.fff", font.name()));
+            let raw = font.data.to_bytes()?;
+            if !cache.is_fresh(&path, hash_of(&raw)) {
+                std::fs::write(&path, raw).context("dump font")?;
+                updated.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        })?;
+    Ok(updated.into_inner())
+}
 
-    let atlases = load_atlases().context("load atlases")?;
-    let fonts = get_fonts(&atlases);
-    let dir_path = root.join("ascii");
-    let encoding_index = 0;
-    for font in fonts {
-        let size = &font.font.character_size;
-        let file_name = format!("{}_{}x{}.fff", font.family, size.width, size.height);
-        let path = dir_path.join(file_name);
-        dump_font(&path, encoding_index, &font.font).context("dump font")?;
-        count += 1
-    }
+/// Get the index of the encoding with the given name.
+pub(crate) fn encoding_index(encoding_name: &str) -> Option<usize> {
+    ENCODINGS.iter().position(|(e, _)| *e == encoding_name)
+}
+
+/// Get the name of the encoding with the given index.
+pub(crate) fn encoding_name(encoding_index: usize) -> Option<&'static str> {
+    ENCODINGS.get(encoding_index).map(|(e, _)| *e)
+}
+
+/// Get the glyph mapping for the encoding with the given index.
+pub(crate) fn glyph_mapping(encoding_index: u8) -> Option<&'static StrGlyphMapping<'static>> {
+    ENCODINGS.get(usize::from(encoding_index)).map(|(_, m)| *m)
+}
 
-    Ok(count)
+/// Serialize the font into bytes in the Firefly Font Format.
+pub(crate) fn font_to_bytes(encoding_index: usize, font: &MonoFont) -> Vec<u8> {
+    let mut raw = Vec::new();
+    write_font(&mut raw, encoding_index, font).unwrap();
+    raw
 }
 
-fn dump_font(path: &Path, encoding_index: usize, font: &MonoFont) -> io::Result<()> {
-    let mut file = std::fs::File::create(path)?;
-    let f = &mut file;
+fn write_font<W: Write>(f: &mut W, encoding_index: usize, font: &MonoFont) -> io::Result<()> {
     assert_eq!(font.image.size().width % 8, 0);
     write_u8(f, 0x11)?;
     write_u8(f, encoding_index as u8)?;
@@ -71,29 +94,29 @@ fn dump_font(path: &Path, encoding_index: usize, font: &MonoFont) -> io::Result<
     write_u8(f, font.character_size.height as u8)?;
     write_u8(f, font.baseline as u8)?;
     write_u16(f, font.image.size().width as u16)?;
-    let mut target = FileWrapper { file };
+    let mut target = FileWrapper { file: f };
     font.image.draw(&mut target)
 }
 
-fn write_u8(f: &mut File, v: u8) -> io::Result<()> {
+fn write_u8<W: Write>(f: &mut W, v: u8) -> io::Result<()> {
     f.write_all(&v.to_le_bytes())
 }
 
-fn write_u16(f: &mut File, v: u16) -> io::Result<()> {
+fn write_u16<W: Write>(f: &mut W, v: u16) -> io::Result<()> {
     f.write_all(&v.to_le_bytes())
 }
 
-struct FileWrapper {
-    file: File,
+struct FileWrapper<'a, W: Write> {
+    file: &'a mut W,
 }
 
-impl OriginDimensions for FileWrapper {
+impl<W: Write> OriginDimensions for FileWrapper<'_, W> {
     fn size(&self) -> Size {
         unimplemented!("not implemented")
     }
 }
 
-impl DrawTarget for FileWrapper {
+impl<W: Write> DrawTarget for FileWrapper<'_, W> {
     type Color = BinaryColor;
     type Error = io::Error;
 
@@ -128,6 +151,9 @@ impl DrawTarget for FileWrapper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::catalog::build_catalog;
+    use crate::config::{load_config, BuildOptions};
+    use crate::manifest::Manifest;
     use embedded_graphics::mono_font::ascii;
 
     const HEADER_SIZE: usize = 7;
@@ -135,18 +161,19 @@ mod tests {
     #[test]
     fn test_save_all_fonts() {
         let path = std::env::temp_dir().join("test_save_all_fonts");
-        save_all_fonts(&path).unwrap();
+        let (config, _) = load_config(&BuildOptions::default()).unwrap();
+        let fonts = build_catalog(&Manifest::default(), &config).unwrap();
+        let cache = BuildCache::load(&path);
+        save_all_fonts(&path, &fonts, &cache).unwrap();
         let iter = std::fs::read_dir(&path).unwrap();
-        // 14 encodings, a separate dir for each encoding
-        assert_eq!(iter.count(), 14);
+        // 22 encodings, a separate dir for each encoding
+        assert_eq!(iter.count(), 22);
     }
 
     #[test]
     fn test_dump_font() {
         let font = ascii::FONT_5X7;
-        let path = std::env::temp_dir().join("test_dump_font");
-        dump_font(&path, 0, &font).unwrap();
-        let dumped = std::fs::read(&path).unwrap();
+        let dumped = font_to_bytes(0, &font);
         assert_eq!(dumped.len(), 420 + HEADER_SIZE);
         assert_eq!(dumped[0], u8::to_le_bytes(0x11)[0]);
     }
@@ -0,0 +1,103 @@
+use crate::bitmap::{pack_rows, BitmapFont, Glyph};
+use crate::import::{initializer, parse_bytes, parse_int, split_items};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+/// Parse an Adafruit GFX font header (comments already stripped).
+///
+/// The header has three declarations: the bitmaps (`uint8_t[]`),
+/// the glyph table (`GFXglyph[]`: bitmap offset, width, height,
+/// x advance, x offset, y offset), and the font (`GFXfont`: pointers to both,
+/// the first and the last character, and the line height).
+/// Bitmaps are not padded: rows of a glyph follow each other bit by bit.
+pub(crate) fn parse(src: &str) -> Result<BitmapFont> {
+    let bitmaps = initializer(src, "uint8_t").context("bitmaps not found")?;
+    let bitmaps = parse_bytes(bitmaps).context("parse bitmaps")?;
+    let table = initializer(src, "GFXglyph").context("glyph table not found")?;
+    let font = initializer(src, "GFXfont").context("font declaration not found")?;
+    let font = split_items(font);
+    let [.., first, _last, _line_height] = font[..] else {
+        bail!("invalid font declaration");
+    };
+    let first = parse_int(first).context("parse first character")?;
+
+    let mut glyphs = HashMap::new();
+    for (i, entry) in split_items(table).into_iter().enumerate() {
+        let fields = split_items(entry)
+            .into_iter()
+            .map(parse_int)
+            .collect::<Result<Vec<_>>>()
+            .context("parse glyph table")?;
+        let [offset, width, height, advance, x_offset, y_offset] = fields[..] else {
+            bail!("invalid glyph table entry: {entry}");
+        };
+        let Some(c) = u32::try_from(first + i as i64)
+            .ok()
+            .and_then(char::from_u32)
+        else {
+            bail!("invalid character code: {}", first + i as i64);
+        };
+        let (width, height) = (width as u32, height as u32);
+        let offset = offset as usize;
+        if (offset * 8 + (width * height) as usize).div_ceil(8) > bitmaps.len() {
+            bail!("glyph bitmap for {c:?} is out of bounds");
+        }
+        let bit = |i: usize| {
+            let i = offset * 8 + i;
+            bitmaps[i / 8] & (0x80 >> (i % 8)) != 0
+        };
+        let glyph = Glyph {
+            width,
+            height,
+            x_offset: x_offset as i32,
+            // GFX offsets are from the baseline to the top-left corner, y goes down.
+            y_offset: -(y_offset as i32 + height as i32),
+            advance: advance as u32,
+            rows: pack_rows(width, height, bit),
+        };
+        glyphs.insert(c, glyph);
+    }
+    Ok(BitmapFont::new(glyphs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A proportional font with "!" (1px wide) and "A" (3px wide with a descender pixel).
+    const SOURCE: &str = "
+const uint8_t TestBitmaps[] PROGMEM = {
+  0xE8, 0x57, 0xD8 };
+
+const GFXglyph TestGlyphs[] PROGMEM = {
+  {     0,   1,   4,   3,    1,   -4 },
+  {     1,   3,   5,   4,    0,   -4 } };
+
+const GFXfont Test PROGMEM = {
+  (uint8_t  *)TestBitmaps,
+  (GFXglyph *)TestGlyphs,
+  0x21, 0x22, 7 };
+";
+
+    #[test]
+    fn test_parse() {
+        let font = parse(SOURCE).unwrap();
+        assert_eq!(font.glyphs.len(), 2);
+        assert!(!font.is_fixed_cell());
+        // 4px above the baseline and 1px below.
+        assert_eq!((font.width, font.height, font.baseline()), (4, 5, 3));
+        let render = |c| {
+            let mut result = String::new();
+            for y in 0..font.height {
+                for x in 0..font.width {
+                    result.push(if font.pixel(c, x, y) { '#' } else { '.' });
+                }
+                result.push('\n');
+            }
+            result
+        };
+        // "!" is centered: (4 - 3) / 2 = 0, plus the x offset of 1.
+        assert_eq!(render('!'), ".#..\n.#..\n.#..\n....\n....\n");
+        assert_eq!(render('"'), ".#..\n#.#.\n###.\n#.#.\n#...\n");
+    }
+}
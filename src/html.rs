@@ -1,8 +1,16 @@
-use crate::extra_fonts::{get_fonts, load_atlases, License};
-use crate::fonts::FONTS;
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font, Style};
+use crate::compare::{comparable, Entry, GROUPS};
+use crate::config::Config;
+use crate::extra_fonts::License;
+use crate::layout::sample;
+use crate::licenses::LicenseEntry;
+use crate::snippets::{snippets, Snippet};
 use anyhow::{Context, Result};
 use minijinja::{context, Environment};
+use rayon::prelude::*;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -77,32 +85,128 @@ static ENCODINGS: &[Encoding] = &[
         title: "Japanese katakana (halfwidth).",
         icon: "🇯🇵",
     },
+    Encoding {
+        slug: "cp437",
+        title: "Code page 437 (DOS), box drawing.",
+        icon: "🖥️",
+    },
+    Encoding {
+        slug: "koi8_r",
+        title: "KOI8-R, Russian.",
+        icon: "🇷🇺",
+    },
+    Encoding {
+        slug: "koi8_u",
+        title: "KOI8-U, Ukrainian.",
+        icon: "🇺🇦",
+    },
+    Encoding {
+        slug: "cp1251",
+        title: "Windows-1251, Cyrillic.",
+        icon: "🇧🇬",
+    },
+    Encoding {
+        slug: "iso_8859_8",
+        title: "Latin/Hebrew.",
+        icon: "🇮🇱",
+    },
+    Encoding {
+        slug: "iso_8859_6",
+        title: "Latin/Arabic.",
+        icon: "🇸🇦",
+    },
+    Encoding {
+        slug: "iso_8859_11",
+        title: "Latin/Thai (TIS-620).",
+        icon: "🇹🇭",
+    },
+    Encoding {
+        slug: "unicode",
+        title: "Unicode, large character sets.",
+        icon: "🌐",
+    },
 ];
 
-static EG_LICENSE: License = License {
-    spdx: "MIT",
-    url: "https://github.com/embedded-graphics/embedded-graphics/blob/master/README.md#license",
-};
+#[derive(Serialize)]
+struct Encoding {
+    slug: &'static str,
+    title: &'static str,
+    icon: &'static str,
+}
 
+/// A static page with some of the fonts of an encoding.
 #[derive(Serialize)]
-struct Font {
-    family: &'static str,
-    width: u32,
-    height: u32,
-    encoding: &'static str,
-    license: License,
+struct Facet {
+    slug: &'static str,
+    title: &'static str,
+    #[serde(skip)]
+    includes: fn(&Font) -> bool,
 }
 
+static FACETS: &[Facet] = &[
+    Facet {
+        slug: "small",
+        title: "up to 6px tall",
+        includes: |f| f.height <= 6,
+    },
+    Facet {
+        slug: "medium",
+        title: "7 to 10px tall",
+        includes: |f| (7..=10).contains(&f.height),
+    },
+    Facet {
+        slug: "large",
+        title: "over 10px tall",
+        includes: |f| f.height > 10,
+    },
+    Facet {
+        slug: "regular",
+        title: "regular",
+        includes: |f| f.style == Style::Regular,
+    },
+    Facet {
+        slug: "bold",
+        title: "bold",
+        includes: |f| f.style == Style::Bold,
+    },
+    Facet {
+        slug: "italic",
+        title: "italic",
+        includes: |f| f.style == Style::Italic,
+    },
+    Facet {
+        slug: "permissive",
+        title: "no copyleft licenses",
+        includes: |f| !f.licenses.iter().any(License::is_copyleft),
+    },
+    Facet {
+        slug: "complete",
+        title: "all glyphs drawn",
+        includes: |f| f.coverage == 100,
+    },
+];
+
+/// A comparison page.
 #[derive(Serialize)]
-struct Encoding {
+struct Comparison<'a> {
     slug: &'static str,
     title: &'static str,
-    icon: &'static str,
+    fonts: Vec<Entry<'a>>,
 }
 
-pub(crate) fn build_html(root: &Path) -> Result<()> {
+/// Render all HTML pages.
+///
+/// Returns how many pages were updated.
+pub(crate) fn build_html(
+    config: &Config,
+    all_fonts: &[Font],
+    licenses: &[LicenseEntry],
+    cache: &BuildCache,
+) -> Result<usize> {
+    let root = &config.output;
     let mut env = Environment::new();
-    let templates = fs::read_dir("templates")?;
+    env.add_global("base_url", config.base_url.as_str());
+    let templates = fs::read_dir(&config.templates).context("read templates dir")?;
     for file_path in templates {
         let file_path = file_path?;
         let content = fs::read_to_string(file_path.path())?;
@@ -111,59 +215,127 @@ pub(crate) fn build_html(root: &Path) -> Result<()> {
         env.add_template_owned(file_name, content)?;
     }
 
-    let all_fonts = make_fonts().context("make fonts")?;
-
+    let encodings: Vec<&Encoding> = ENCODINGS
+        .iter()
+        .filter(|e| config.includes_encoding(e.slug))
+        .collect();
+    let comparable = comparable(all_fonts);
+    let groups: Vec<Comparison> = GROUPS
+        .iter()
+        .map(|g| Comparison {
+            slug: g.slug,
+            title: g.title,
+            fonts: g.entries(&comparable),
+        })
+        .filter(|g| !g.fonts.is_empty())
+        .collect();
+    // Cards warn about copyleft licenses and link to their section on the licenses page.
+    let copyleft: Vec<&str> = licenses
+        .iter()
+        .filter(|l| l.info.copyleft)
+        .map(|l| l.info.spdx)
+        .collect();
+    let mut updated = 0;
     {
         let out_path = root.join("index.html");
         let tmpl = env.get_template("index.html.j2").context("get template")?;
         let rendered = tmpl
-            .render(context!(encodings => ENCODINGS))
+            .render(context!(encodings => encodings, groups => groups, page => "index.html"))
             .context("render template")?;
-        fs::write(out_path, rendered).context("write html file")?;
+        updated += write_page(&out_path, &rendered, cache)?;
     }
 
-    let tmpl = env
-        .get_template("encoding.html.j2")
-        .context("get template")?;
-    for encoding in ENCODINGS {
-        let fonts: Vec<_> = all_fonts
-            .iter()
-            .filter(|f| f.encoding == encoding.slug)
-            .collect();
-        let out_path = root.join(format!("{}.html", encoding.slug));
+    {
+        let out_path = root.join("licenses.html");
+        let tmpl = env
+            .get_template("licenses.html.j2")
+            .context("get template")?;
         let rendered = tmpl
-            .render(context!(fonts => fonts, encoding => encoding))
+            .render(context!(licenses => licenses, page => "licenses.html"))
             .context("render template")?;
-        fs::write(out_path, rendered).context("write html file")?;
+        updated += write_page(&out_path, &rendered, cache)?;
     }
 
-    Ok(())
-}
-
-fn make_fonts() -> Result<Vec<Font>> {
-    let mut result = Vec::new();
-    let atlases = load_atlases().context("load atlases")?;
-    let fonts = get_fonts(&atlases);
-    for font in fonts {
-        result.push(Font {
-            family: font.family,
-            encoding: "ascii",
-            width: font.font.character_size.width,
-            height: font.font.character_size.height,
-            license: font.license,
-        })
+    let tmpl = env
+        .get_template("compare.html.j2")
+        .context("get template")?;
+    for group in &groups {
+        let page = format!("compare-{}.html", group.slug);
+        let rendered = tmpl
+            .render(context!(group => group, groups => groups, page => page))
+            .context("render template")?;
+        updated += write_page(&root.join(&page), &rendered, cache)?;
     }
 
-    for (family, encoding, fonts) in FONTS {
-        for font in *fonts {
-            result.push(Font {
-                family,
-                encoding,
-                width: font.character_size.width,
-                height: font.character_size.height,
-                license: EG_LICENSE,
+    let tmpl = env
+        .get_template("encoding.html.j2")
+        .context("get template")?;
+    // Fonts that share a file are in the archive once.
+    let files = unique_files(all_fonts);
+    // Encoding pages are most of the site, so they are rendered in parallel.
+    let pages = encodings.par_iter().map(|encoding| -> Result<usize> {
+        let fonts: Vec<&Font> = all_fonts
+            .iter()
+            .filter(|f| f.encoding == encoding.slug)
+            .collect();
+        let files = files.iter().filter(|f| f.encoding == encoding.slug).count();
+        let sample = sample(encoding.slug);
+        // Fonts with the same file name have the same snippets.
+        let snippets: BTreeMap<String, Vec<Snippet>> = fonts
+            .iter()
+            .map(|f| Ok((f.name(), snippets(f)?)))
+            .collect::<Result<_>>()?;
+        // Facet pages are for browsers without JavaScript, so they are sorted by size
+        // the same way the filters on the main page sort them.
+        let mut by_size = fonts.clone();
+        by_size.sort_by_key(|f| (f.height, f.width));
+        let facets: Vec<(&Facet, Vec<&Font>)> = FACETS
+            .iter()
+            .map(|facet| {
+                let fonts = by_size.iter().copied().filter(|f| (facet.includes)(f));
+                (facet, fonts.collect::<Vec<_>>())
             })
+            .filter(|(_, fonts)| !fonts.is_empty())
+            .collect();
+        let links: Vec<&Facet> = facets.iter().map(|(facet, _)| *facet).collect();
+        let render = |fonts: &[&Font], facet: Option<&Facet>| -> Result<usize> {
+            let page = match facet {
+                Some(facet) => format!("{}-{}.html", encoding.slug, facet.slug),
+                None => format!("{}.html", encoding.slug),
+            };
+            let rendered = tmpl
+                .render(context!(
+                    fonts => fonts,
+                    files => files,
+                    encoding => encoding,
+                    sample => sample,
+                    snippets => snippets,
+                    copyleft => copyleft,
+                    facet => facet,
+                    facets => links,
+                    page => page,
+                ))
+                .context("render template")?;
+            write_page(&root.join(&page), &rendered, cache)
+        };
+        let mut updated = render(&fonts, None)?;
+        for (facet, fonts) in &facets {
+            updated += render(fonts, Some(facet))?;
         }
+        Ok(updated)
+    });
+    updated += pages
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .sum::<usize>();
+    Ok(updated)
+}
+
+/// Write the page if its content has changed since the last build.
+fn write_page(path: &Path, rendered: &str, cache: &BuildCache) -> Result<usize> {
+    if cache.is_fresh(path, hash_of(rendered)) {
+        return Ok(0);
     }
-    Ok(result)
+    fs::write(path, rendered).context("write html file")?;
+    Ok(1)
 }
@@ -1,49 +1,146 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::{unique_files, Font};
+use crate::fff::Fff;
+use crate::layout::{layout_line, sample};
 use anyhow::{Context, Result};
+use rayon::prelude::*;
 use std::fs;
 use std::io::BufWriter;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub(crate) fn fonts_to_images(in_dir: &Path, out_dir: &Path) -> Result<()> {
+/// The version of `font_to_image`, `sample_to_image`, and the text layout they use.
+///
+/// It's a part of the cache keys, so bump it when changing how previews are drawn.
+const RENDER_VERSION: u32 = 1;
+
+/// Render previews for all catalog fonts.
+///
+/// Returns how many images were updated.
+pub(crate) fn fonts_to_images(fonts: &[Font], out_dir: &Path, cache: &BuildCache) -> Result<usize> {
     fs::create_dir_all(out_dir).context("create images dir")?;
-    let dirs = fs::read_dir(in_dir).context("read input dir")?;
-    for subdir in dirs {
-        let subdir = subdir.context("access subdir")?;
-        let files = fs::read_dir(subdir.path()).context("read input subdir")?;
-        let out_subdir = out_dir.join(subdir.file_name());
-        fs::create_dir_all(&out_subdir).context("create images subdir")?;
-        for file in files {
-            let file = file.context("access font file")?;
-            let in_path = file.path();
-            let out_path = out_subdir.join(file.file_name()).with_extension("png");
-            font_to_image(&in_path, &out_path).context("convert font to image")?
-        }
+    for font in fonts {
+        fs::create_dir_all(out_dir.join(font.encoding)).context("create images subdir")?;
     }
-    Ok(())
+    let updated = AtomicUsize::new(0);
+    unique_files(fonts)
+        .par_iter()
+        .try_for_each(|font| -> Result<()> {
+            let out_path = out_dir
+                .join(font.encoding)
+                .join(format!("{}.png", font.name()));
+            let bytes = font.data.to_bytes()?;
+            if !cache.is_fresh(&out_path, hash_of(&(&bytes, RENDER_VERSION))) {
+                font_to_image(&font.data, &out_path).context("convert font to image")?;
+                updated.fetch_add(1, Ordering::Relaxed);
+            }
+            if let Some(text) = sample(font.encoding) {
+                let out_path = out_dir.join(sample_path(font));
+                if !cache.is_fresh(&out_path, hash_of(&(&bytes, RENDER_VERSION, text))) {
+                    sample_to_image(&font.data, text, &out_path).context("render sample")?;
+                    updated.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Ok(())
+        })?;
+    Ok(updated.into_inner())
+}
+
+/// The path of the sample text preview, relative to the images dir.
+pub(crate) fn sample_path(font: &Font) -> String {
+    format!("{}/{}.sample.png", font.encoding, font.name())
 }
 
-fn font_to_image(in_path: &Path, out_path: &Path) -> Result<()> {
-    // read font data
-    let raw_font = fs::read(in_path)?;
-    let width = i16::from_le_bytes([raw_font[5], raw_font[6]]) as u32;
-    let data = raw_font.get(7..).unwrap();
-    let height = data.len() as u32 * 8 / width;
-
-    // invert colors
-    let mut inv_data = Vec::new();
-    for byte in data {
-        inv_data.push(!byte)
+fn font_to_image(font: &Fff, out_path: &Path) -> Result<()> {
+    let width = font.atlas_width;
+    let height = font.atlas.len() as u32 * 8 / width;
+    write_png(&font.atlas, width, height, out_path)
+}
+
+/// Render a line of text, with right-to-left runs and combining marks laid out.
+fn sample_to_image(font: &Fff, text: &str, out_path: &Path) -> Result<()> {
+    let placed = layout_line(text);
+    let columns = placed.last().map_or(0, |(_, col)| col + 1);
+    // PNG rows are padded to a whole byte anyway,
+    // and packing is simpler with the width being a multiple of 8.
+    let width = (columns * font.width).next_multiple_of(8).max(8);
+    let height = font.height;
+    let mut data = vec![0u8; (width * height / 8) as usize];
+    for (c, column) in placed {
+        let Some(glyph) = font.glyph_index(c) else {
+            continue;
+        };
+        for y in 0..height {
+            for x in 0..font.width {
+                if font.pixel(glyph, x, y) {
+                    let bit = (y * width + column * font.width + x) as usize;
+                    data[bit / 8] |= 0x80 >> (bit % 8);
+                }
+            }
+        }
     }
+    write_png(&data, width, height, out_path)
+}
+
+/// Write 1-bit packed pixels as a black-on-white PNG.
+pub(crate) fn write_png(data: &[u8], width: u32, height: u32, out_path: &Path) -> Result<()> {
+    let inverted: Vec<u8> = data.iter().map(|byte| !byte).collect();
+    write_bitmap(&inverted, width, height, out_path)
+}
 
-    // write png
+/// Write 1-bit packed pixels as a white-on-black PNG, the way atlases are stored.
+pub(crate) fn write_bitmap(data: &[u8], width: u32, height: u32, out_path: &Path) -> Result<()> {
     let file = fs::File::create(out_path).context("create image file")?;
     let buffer = BufWriter::new(file);
-    let mut encoder = png::Encoder::new(buffer, width as u32, height as u32);
+    let mut encoder = png::Encoder::new(buffer, width, height);
     encoder.set_color(png::ColorType::Grayscale);
     encoder.set_depth(png::BitDepth::One);
+    // Explicit settings, so that images stay byte-identical
+    // even if the defaults change in the future versions of the encoder.
+    encoder.set_compression(png::Compression::Default);
+    encoder.set_filter(png::FilterType::NoFilter);
+    encoder.set_adaptive_filter(png::AdaptiveFilterType::NonAdaptive);
     let mut writer = encoder.write_header().context("write PNG header")?;
-    writer
-        .write_image_data(&inv_data)
-        .context("write image data")?;
+    writer.write_image_data(data).context("write image data")?;
+    Ok(())
+}
+
+/// Write palette indices (one byte per pixel) as an indexed PNG,
+/// with each pixel enlarged into a square of the given size.
+pub(crate) fn write_indexed(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    palette: &[[u8; 3]; 16],
+    scale: u32,
+    out_path: &Path,
+) -> Result<()> {
+    let (scaled_width, scaled_height) = (width * scale, height * scale);
+    // 4 bits per pixel, rows padded to a whole byte.
+    let row_size = scaled_width.div_ceil(2) as usize;
+    let mut data = Vec::with_capacity(row_size * scaled_height as usize);
+    for row in pixels.chunks(width as usize) {
+        let mut scaled_row = vec![0u8; row_size];
+        for x in 0..scaled_width {
+            let index = row[(x / scale) as usize] & 0x0F;
+            let shift = if x % 2 == 0 { 4 } else { 0 };
+            scaled_row[x as usize / 2] |= index << shift;
+        }
+        for _ in 0..scale {
+            data.extend_from_slice(&scaled_row);
+        }
+    }
 
+    let file = fs::File::create(out_path).context("create image file")?;
+    let buffer = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(buffer, scaled_width, scaled_height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Four);
+    encoder.set_palette(palette.concat());
+    encoder.set_compression(png::Compression::Default);
+    encoder.set_filter(png::FilterType::NoFilter);
+    encoder.set_adaptive_filter(png::AdaptiveFilterType::NonAdaptive);
+    let mut writer = encoder.write_header().context("write PNG header")?;
+    writer.write_image_data(&data).context("write image data")?;
     Ok(())
 }
@@ -0,0 +1,314 @@
+use crate::bdf;
+use crate::bitmap::{pack_rows, BitmapFont, Glyph};
+use crate::export::SheetMetrics;
+use crate::extra_fonts::load_atlas;
+use crate::gfx;
+use crate::u8g2;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Args)]
+pub(crate) struct ImportArgs {
+    /// The font to convert: a BDF file, an Adafruit GFX header, a U8g2 font source,
+    /// or an atlas image.
+    font: PathBuf,
+
+    /// Where to save the font in FFF.
+    output: PathBuf,
+
+    /// The encoding of the output font, or `unicode` for a Unicode-indexed font
+    /// with all glyphs of the source.
+    #[arg(long, default_value = "ascii")]
+    encoding: String,
+}
+
+pub(crate) fn cmd_import(args: &ImportArgs) -> Result<()> {
+    let font = load(&args.font)?;
+    let fixed = font.is_fixed_cell();
+    let data = font.convert(&args.encoding)?;
+    data.write(&args.output)?;
+    println!(
+        "Imported {} glyphs into {}x{} cells ({})",
+        font.glyphs.len(),
+        data.width,
+        data.height,
+        if fixed { "fixed-cell" } else { "centered" },
+    );
+    Ok(())
+}
+
+/// Load a font in any of the supported source formats.
+///
+/// BDF and atlases are detected by the extension, C sources by the types they declare.
+pub(crate) fn load(path: &Path) -> Result<BitmapFont> {
+    if path.extension().is_some_and(|e| e == "bdf") {
+        return bdf::read(path);
+    }
+    if path.extension().is_some_and(|e| e == "png") {
+        return read_atlas(path);
+    }
+    let raw = std::fs::read_to_string(path).context("read font source")?;
+    let src = strip_comments(&raw);
+    if src.contains("GFXglyph") {
+        gfx::parse(&src).context("parse Adafruit GFX font")
+    } else if src.contains("u8g2_font_") || src.contains("U8G2_FONT_SECTION") {
+        u8g2::parse(&src).context("parse U8g2 font")
+    } else {
+        bail!("unknown font format: {}", path.display())
+    }
+}
+
+/// Load an atlas image with glyphs in the order of its glyph order file.
+///
+/// The cell size comes from the file name, like `pico8_4x6.png`.
+/// The baseline comes from the metrics next to the atlas, if any (see `export`),
+/// or else it's the bottom row of the cell.
+fn read_atlas(path: &Path) -> Result<BitmapFont> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let size = stem
+        .rsplit_once('_')
+        .and_then(|(_, size)| size.split_once('x'));
+    let Some((Ok(width), Ok(height))) = size.map(|(w, h)| (w.parse::<u32>(), h.parse::<u32>()))
+    else {
+        bail!("no cell size in the file name, expected a name like font_8x8.png");
+    };
+    if width == 0 || height == 0 {
+        bail!("empty cell size in the file name: {width}x{height}");
+    }
+    let atlas = load_atlas(path).context("load atlas")?;
+    let columns = atlas.width / width;
+    let rows = atlas.data.len() as u32 * 8 / atlas.width / height;
+    if columns == 0 || rows == 0 {
+        bail!("atlas is smaller than the cell");
+    }
+    // Atlases from exported sheets have the baseline in their metrics.
+    let y_offset = match SheetMetrics::read(path)? {
+        Some(metrics) => metrics.baseline as i32 + 1 - height as i32,
+        None => 0,
+    };
+    let order = &atlas.order;
+    // Lowercase letters are looked up too: orders without them use uppercase ones.
+    let chars = order
+        .chars
+        .iter()
+        .flatten()
+        .flat_map(|&c| std::iter::once(c).chain(c.to_lowercase()));
+    let mut glyphs = HashMap::new();
+    for c in chars {
+        let Some(index) = order.position(c) else {
+            continue;
+        };
+        let index = index as u32;
+        if glyphs.contains_key(&c) || index >= columns * rows {
+            continue;
+        }
+        let (left, top) = (index % columns * width, index / columns * height);
+        let bit = |i: usize| {
+            let (x, y) = (left + i as u32 % width, top + i as u32 / width);
+            let bit = (y * atlas.width + x) as usize;
+            atlas.data[bit / 8] & (0x80 >> (bit % 8)) != 0
+        };
+        let glyph = Glyph {
+            width,
+            height,
+            x_offset: 0,
+            y_offset,
+            advance: width,
+            rows: pack_rows(width, height, bit),
+        };
+        glyphs.insert(c, glyph);
+    }
+    Ok(BitmapFont::new(glyphs))
+}
+
+/// Remove `//` and `/* */` comments from C source.
+fn strip_comments(src: &str) -> String {
+    let mut result = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            match c {
+                '\\' => result.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        result.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                result.push(' ');
+            }
+            _ => {
+                in_string = c == '"';
+                result.push(c);
+            }
+        }
+    }
+    result
+}
+
+/// The initializer of the first variable declared with the given type,
+/// like `{ 0x00, 0x7E }` or `"\2\3..."` in `const uint8_t font[] = ...;`.
+///
+/// Casts (like `(uint8_t *)`) are not declarations and are skipped.
+pub(crate) fn initializer<'a>(src: &'a str, type_name: &str) -> Option<&'a str> {
+    let mut rest = src;
+    while let Some(pos) = rest.find(type_name) {
+        let after = &rest[pos + type_name.len()..];
+        let is_declaration = after.starts_with(char::is_whitespace)
+            && after
+                .trim_start()
+                .starts_with(|c: char| c.is_alphabetic() || c == '_');
+        rest = after;
+        if !is_declaration {
+            continue;
+        }
+        let start = after.find('=')?;
+        let end = after[start..].find(';')? + start;
+        return Some(after[start + 1..end].trim());
+    }
+    None
+}
+
+/// Split the body of a brace initializer by top-level commas.
+pub(crate) fn split_items(init: &str) -> Vec<&str> {
+    let init = init.trim();
+    let body = init
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .unwrap_or(init);
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(body[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(body[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Parse a C integer literal: decimal, hex, or octal, possibly negative.
+pub(crate) fn parse_int(literal: &str) -> Result<i64> {
+    let literal = literal.trim().trim_end_matches(['u', 'U', 'l', 'L']);
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(digits) => (true, digits.trim()),
+        None => (false, literal),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8)
+    } else {
+        digits.parse()
+    };
+    let value = value.with_context(|| format!("invalid integer: {literal}"))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Bytes of a C initializer: either an array of numbers or concatenated string literals.
+pub(crate) fn parse_bytes(init: &str) -> Result<Vec<u8>> {
+    if init.starts_with('{') {
+        return split_items(init)
+            .into_iter()
+            .map(|item| Ok(parse_int(item)? as u8))
+            .collect();
+    }
+    let mut result = Vec::new();
+    let mut chars = init.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if !in_string {
+            in_string = c == '"';
+            continue;
+        }
+        match c {
+            '"' => in_string = false,
+            '\\' => {
+                let Some(e) = chars.next() else {
+                    bail!("unterminated escape sequence");
+                };
+                let byte = match e {
+                    'n' => b'\n',
+                    't' => b'\t',
+                    'r' => b'\r',
+                    'a' => 0x07,
+                    'b' => 0x08,
+                    'f' => 0x0C,
+                    'v' => 0x0B,
+                    'x' => {
+                        let mut value = 0u32;
+                        while let Some(d) = chars.peek().and_then(|d| d.to_digit(16)) {
+                            value = value * 16 + d;
+                            chars.next();
+                        }
+                        value as u8
+                    }
+                    '0'..='7' => {
+                        let mut value = e.to_digit(8).unwrap();
+                        for _ in 0..2 {
+                            let Some(d) = chars.peek().and_then(|d| d.to_digit(8)) else {
+                                break;
+                            };
+                            value = value * 8 + d;
+                            chars.next();
+                        }
+                        value as u8
+                    }
+                    other => other as u8,
+                };
+                result.push(byte);
+            }
+            c => {
+                let mut buf = [0; 4];
+                result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bytes() {
+        let src =
+            strip_comments("const uint8_t f[3] /* size */ = \"A\\2\\377\" // end\n \"\\x41\";");
+        let init = initializer(&src, "uint8_t").unwrap();
+        assert_eq!(parse_bytes(init).unwrap(), vec![b'A', 2, 0xFF, 0x41]);
+        let init = "{ 0x10, 7, -1, 010 }";
+        assert_eq!(parse_bytes(init).unwrap(), vec![0x10, 7, 0xFF, 8]);
+    }
+}
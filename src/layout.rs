@@ -0,0 +1,144 @@
+/// Sample text for previews of encodings that need special layout.
+static SAMPLES: &[(&str, &str)] = &[
+    ("iso_8859_8", "שלום עולם! 123"),
+    ("iso_8859_6", "مرحبا بالعالم! 123"),
+    ("iso_8859_11", "สวัสดีชาวโลก 123"),
+];
+
+/// The sample text for the encoding, if it has one.
+pub(crate) fn sample(encoding: &str) -> Option<&'static str> {
+    SAMPLES
+        .iter()
+        .find(|(e, _)| *e == encoding)
+        .map(|(_, s)| *s)
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Direction {
+    Ltr,
+    Rtl,
+    Neutral,
+}
+
+/// Lay out a line of text for a monospace font.
+///
+/// Returns the characters in visual order, each with the column it's drawn at.
+/// Combining marks are drawn in the same column as the preceding character,
+/// and runs of right-to-left characters (Hebrew and Arabic) are reversed.
+///
+/// This is a simplified version of the Unicode bidirectional algorithm:
+/// the paragraph direction is defined by the first strong character,
+/// numbers always go left-to-right, and there is no bracket mirroring
+/// and no explicit embeddings. Direction marks (LRM and RLM) affect the order
+/// but take no space.
+pub(crate) fn layout_line(text: &str) -> Vec<(char, u32)> {
+    // Group combining marks with their base characters.
+    let mut clusters: Vec<Vec<char>> = Vec::new();
+    for c in text.chars() {
+        match clusters.last_mut() {
+            Some(cluster) if is_combining(c) => cluster.push(c),
+            _ => clusters.push(vec![c]),
+        }
+    }
+
+    let mut directions: Vec<Direction> = clusters.iter().map(|c| direction(c[0])).collect();
+    let paragraph = directions
+        .iter()
+        .copied()
+        .find(|d| *d != Direction::Neutral)
+        .unwrap_or(Direction::Ltr);
+    // Neutral characters between two strong characters of the same direction
+    // take that direction, others take the paragraph direction.
+    for i in 0..directions.len() {
+        if directions[i] != Direction::Neutral {
+            continue;
+        }
+        let strong = |d: &&Direction| **d != Direction::Neutral;
+        let before = directions[..i].iter().rev().find(strong);
+        let after = directions[i + 1..].iter().find(strong);
+        directions[i] = match (before, after) {
+            (Some(b), Some(a)) if b == a => *b,
+            _ => paragraph,
+        };
+    }
+
+    let mut order: Vec<usize> = (0..clusters.len()).collect();
+    if paragraph == Direction::Rtl {
+        order.reverse();
+    }
+    // Reverse runs that go against the paragraph direction.
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end < order.len() && directions[order[end]] != paragraph {
+            end += 1;
+        }
+        order[start..end].reverse();
+        start = end + 1;
+    }
+
+    let mut result = Vec::new();
+    let mut column = 0;
+    for i in order {
+        let cluster = &clusters[i];
+        if matches!(cluster[0], '\u{200e}' | '\u{200f}') {
+            continue;
+        }
+        for &c in cluster {
+            result.push((c, column));
+        }
+        column += 1;
+    }
+    result
+}
+
+fn direction(c: char) -> Direction {
+    match c {
+        '\u{200f}' => Direction::Rtl,
+        '\u{200e}' => Direction::Ltr,
+        // Arabic-Indic digits, written left-to-right like other numbers.
+        '\u{660}'..='\u{669}' | '\u{6f0}'..='\u{6f9}' => Direction::Ltr,
+        '\u{590}'..='\u{8ff}' | '\u{fb1d}'..='\u{fdff}' | '\u{fe70}'..='\u{feff}' => Direction::Rtl,
+        c if c.is_alphanumeric() => Direction::Ltr,
+        _ => Direction::Neutral,
+    }
+}
+
+/// If the character is a combining mark drawn over or under the preceding character.
+fn is_combining(c: char) -> bool {
+    matches!(c,
+        '\u{300}'..='\u{36f}'
+        // Hebrew points and cantillation marks
+        | '\u{591}'..='\u{5bd}' | '\u{5bf}' | '\u{5c1}' | '\u{5c2}' | '\u{5c4}' | '\u{5c5}' | '\u{5c7}'
+        // Arabic harakat
+        | '\u{64b}'..='\u{65f}' | '\u{670}'
+        // Thai vowels and tone marks
+        | '\u{e31}' | '\u{e34}'..='\u{e3a}' | '\u{e47}'..='\u{e4e}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visual(text: &str) -> String {
+        layout_line(text).into_iter().map(|(c, _)| c).collect()
+    }
+
+    #[test]
+    fn test_rtl() {
+        assert_eq!(visual("abc"), "abc");
+        assert_eq!(visual("שלום"), "םולש");
+        assert_eq!(visual("שלום 123!"), "!123 םולש");
+        assert_eq!(visual("a שלום b"), "a םולש b");
+        assert_eq!(visual("שלום abc שלום"), "םולש abc םולש");
+    }
+
+    #[test]
+    fn test_combining() {
+        // ส + วั + ส + ดี
+        let placed = layout_line("สวัสดี");
+        let columns: Vec<u32> = placed.iter().map(|(_, col)| *col).collect();
+        assert_eq!(columns, vec![0, 1, 1, 2, 3, 3]);
+    }
+}
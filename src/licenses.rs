@@ -0,0 +1,255 @@
+use crate::cache::{hash_of, BuildCache};
+use crate::catalog::Font;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// What game authors need to know about a license fonts can have.
+#[derive(Serialize)]
+pub(crate) struct LicenseInfo {
+    pub spdx: &'static str,
+    pub name: &'static str,
+    /// The canonical license text, if there is one apart from the bundled copy.
+    pub url: Option<&'static str>,
+    /// If works that include the font must be shared under the same license.
+    pub copyleft: bool,
+    /// What copies of the font must come with, if anything.
+    pub attribution: Option<&'static str>,
+}
+
+const NOTICE: &str = "Keep the copyright notice and the license text with copies of the font.";
+const COPYLEFT: &str = "Keep the copyright notice and the license text with copies of the font. \
+    A game that includes the font may have to be shared under the same license, \
+    with its source code.";
+
+/// Licenses that catalog fonts can have.
+///
+/// Adding a font with a license not listed here fails the build.
+/// Each license also needs its text in `licenses/<SPDX ID>.txt`.
+static LICENSES: &[LicenseInfo] = &[
+    LicenseInfo {
+        spdx: "CC0-1.0",
+        name: "Creative Commons Zero v1.0 Universal",
+        url: Some("https://spdx.org/licenses/CC0-1.0.html"),
+        copyleft: false,
+        attribution: None,
+    },
+    LicenseInfo {
+        spdx: "LicenseRef-Public-Domain",
+        name: "Public domain",
+        url: None,
+        copyleft: false,
+        attribution: None,
+    },
+    LicenseInfo {
+        spdx: "Unlicense",
+        name: "The Unlicense",
+        url: Some("https://spdx.org/licenses/Unlicense.html"),
+        copyleft: false,
+        attribution: None,
+    },
+    LicenseInfo {
+        spdx: "MIT",
+        name: "MIT License",
+        url: Some("https://spdx.org/licenses/MIT.html"),
+        copyleft: false,
+        attribution: Some(NOTICE),
+    },
+    LicenseInfo {
+        spdx: "BSD-3-Clause",
+        name: "BSD 3-Clause \"New\" or \"Revised\" License",
+        url: Some("https://spdx.org/licenses/BSD-3-Clause.html"),
+        copyleft: false,
+        attribution: Some(NOTICE),
+    },
+    LicenseInfo {
+        spdx: "Apache-2.0",
+        name: "Apache License 2.0",
+        url: Some("https://spdx.org/licenses/Apache-2.0.html"),
+        copyleft: false,
+        attribution: Some(NOTICE),
+    },
+    LicenseInfo {
+        spdx: "CC-BY-4.0",
+        name: "Creative Commons Attribution 4.0 International",
+        url: Some("https://spdx.org/licenses/CC-BY-4.0.html"),
+        copyleft: false,
+        attribution: Some("Credit the author and link to the license."),
+    },
+    LicenseInfo {
+        spdx: "OFL-1.1",
+        name: "SIL Open Font License 1.1",
+        url: Some("https://spdx.org/licenses/OFL-1.1.html"),
+        copyleft: false,
+        attribution: Some(NOTICE),
+    },
+    LicenseInfo {
+        spdx: "CC-BY-SA-4.0",
+        name: "Creative Commons Attribution Share Alike 4.0 International",
+        url: Some("https://spdx.org/licenses/CC-BY-SA-4.0.html"),
+        copyleft: true,
+        attribution: Some(
+            "Credit the author, link to the license, and share adaptations under the same license.",
+        ),
+    },
+    LicenseInfo {
+        spdx: "GPL-3.0-only",
+        name: "GNU General Public License v3.0 only",
+        url: Some("https://spdx.org/licenses/GPL-3.0-only.html"),
+        copyleft: true,
+        attribution: Some(COPYLEFT),
+    },
+    LicenseInfo {
+        spdx: "GPL-3.0-or-later",
+        name: "GNU General Public License v3.0 or later",
+        url: Some("https://spdx.org/licenses/GPL-3.0-or-later.html"),
+        copyleft: true,
+        attribution: Some(COPYLEFT),
+    },
+    LicenseInfo {
+        spdx: "AGPL-3.0-only",
+        name: "GNU Affero General Public License v3.0 only",
+        url: Some("https://spdx.org/licenses/AGPL-3.0-only.html"),
+        copyleft: true,
+        attribution: Some(COPYLEFT),
+    },
+    LicenseInfo {
+        spdx: "AGPL-3.0-or-later",
+        name: "GNU Affero General Public License v3.0 or later",
+        url: Some("https://spdx.org/licenses/AGPL-3.0-or-later.html"),
+        copyleft: true,
+        attribution: Some(COPYLEFT),
+    },
+];
+
+/// Deprecated SPDX IDs and what to use instead.
+static DEPRECATED: &[(&str, &str)] = &[
+    ("AGPL-3.0", "AGPL-3.0-only or AGPL-3.0-or-later"),
+    ("GPL-2.0", "GPL-2.0-only or GPL-2.0-or-later"),
+    ("GPL-3.0", "GPL-3.0-only or GPL-3.0-or-later"),
+    ("LGPL-2.1", "LGPL-2.1-only or LGPL-2.1-or-later"),
+    ("LGPL-3.0", "LGPL-3.0-only or LGPL-3.0-or-later"),
+];
+
+/// Find the license by its SPDX ID.
+pub(crate) fn license_info(spdx: &str) -> Result<&'static LicenseInfo> {
+    if let Some((_, instead)) = DEPRECATED.iter().find(|(id, _)| *id == spdx) {
+        bail!("deprecated SPDX license ID {spdx}, use {instead}");
+    }
+    match LICENSES.iter().find(|l| l.spdx == spdx) {
+        Some(info) => Ok(info),
+        None => bail!("unknown license {spdx}, add it to LICENSES in src/licenses.rs"),
+    }
+}
+
+/// A license with its full text and the families that have it.
+#[derive(Serialize)]
+pub(crate) struct LicenseEntry {
+    #[serde(flatten)]
+    pub info: &'static LicenseInfo,
+    pub text: String,
+    pub families: Vec<String>,
+}
+
+/// Check licenses of all catalog fonts and read their texts.
+///
+/// Fails if a license is unknown or its text is missing.
+pub(crate) fn collect_licenses(fonts: &[Font], licenses_dir: &Path) -> Result<Vec<LicenseEntry>> {
+    let mut families: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for font in fonts {
+        for license in &font.licenses {
+            let families = families.entry(&license.spdx).or_default();
+            if !families.contains(&font.family) {
+                families.push(font.family.clone());
+            }
+        }
+    }
+    let mut result = Vec::new();
+    for (spdx, mut families) in families {
+        let info =
+            license_info(spdx).with_context(|| format!("check license of {}", families[0]))?;
+        let path = licenses_dir.join(format!("{spdx}.txt"));
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("read license text for {spdx}"))?;
+        families.sort();
+        result.push(LicenseEntry {
+            info,
+            text,
+            families,
+        });
+    }
+    Ok(result)
+}
+
+/// Copy license texts next to the fonts, so that each font download has them.
+///
+/// Returns how many files were updated.
+pub(crate) fn write_license_texts(
+    out_dir: &Path,
+    licenses: &[LicenseEntry],
+    cache: &BuildCache,
+) -> Result<usize> {
+    std::fs::create_dir_all(out_dir).context("create licenses dir")?;
+    let mut updated = 0;
+    for license in licenses {
+        let path = out_dir.join(format!("{}.txt", license.info.spdx));
+        if !cache.is_fresh(&path, hash_of(&license.text)) {
+            std::fs::write(&path, &license.text).context("write license text")?;
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+/// The `NOTICE.txt` file of an archive: the licenses of each font,
+/// where the font comes from, and what copies of it must come with.
+///
+/// Copyleft warnings link the licenses page of the site at `base_url`.
+pub(crate) fn notice(fonts: &[&Font], base_url: &str) -> Result<String> {
+    let mut out = String::new();
+    out.push_str(
+        "Fonts in this archive and their licenses. Full license texts are in LICENSES/.\n",
+    );
+    for font in fonts {
+        out.push('\n');
+        out.push_str(&format!("{}/{}.fff\n", font.encoding, font.name()));
+        for license in &font.licenses {
+            let info = license_info(&license.spdx)?;
+            let text = match &license.url {
+                Some(url) => url.to_string(),
+                None => format!("LICENSES/{}.txt", info.spdx),
+            };
+            out.push_str(&format!("  {} ({}), {text}\n", info.name, info.spdx));
+            if let Some(copyright) = &license.copyright {
+                out.push_str(&format!("  {copyright}\n"));
+            }
+            if let Some(attribution) = info.attribution {
+                out.push_str(&format!("  {attribution}\n"));
+            }
+            if info.copyleft {
+                out.push_str(&format!(
+                    "  Copyleft: games that include this font may be required to be open source, \
+                    see {base_url}licenses.html#{}\n",
+                    info.spdx
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_info() {
+        assert!(license_info("MIT").is_ok());
+        assert!(license_info("AGPL-3.0-only").unwrap().copyleft);
+        assert!(!license_info("CC0-1.0").unwrap().copyleft);
+        let err = license_info("AGPL-3.0").err().unwrap().to_string();
+        assert!(err.contains("AGPL-3.0-only"), "{err}");
+        assert!(license_info("WTFPL").is_err());
+    }
+}
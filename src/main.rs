@@ -1,35 +1,224 @@
+mod archive;
+mod bdf;
+mod bitmap;
+mod blocks;
+mod cache;
+mod catalog;
+mod checksums;
+mod codegen;
+mod compare;
+mod config;
+mod cp437;
+mod credits;
+mod device;
+mod export;
 mod extra_fonts;
+mod fff;
 mod fonts;
 mod generator;
+mod gfx;
 mod html;
 mod image;
+mod import;
+mod layout;
+mod licenses;
+mod manifest;
+mod mappings;
+mod merge;
+mod order;
+mod serve;
+mod sheet;
+mod snippets;
+mod subset;
+mod transform;
+mod u8g2;
+use crate::archive::build_archives;
+use crate::cache::BuildCache;
+use crate::catalog::{build_catalog, Font};
+use crate::checksums::write_checksums;
+use crate::codegen::{cmd_codegen, CodegenArgs};
+use crate::compare::screens_to_images;
+use crate::config::{load_config, BuildOptions, Config};
+use crate::credits::write_credits;
+use crate::device::{cmd_device, devices_to_images, DeviceArgs};
+use crate::export::{cmd_export, ExportArgs};
 use crate::generator::save_all_fonts;
 use crate::html::build_html;
 use crate::image::fonts_to_images;
+use crate::import::{cmd_import, ImportArgs};
+use crate::licenses::{collect_licenses, write_license_texts};
+use crate::serve::{cmd_serve, ServeArgs};
+use crate::sheet::{cmd_sheet, SheetArgs};
+use crate::subset::{cmd_subset, SubsetArgs};
+use crate::transform::{cmd_transform, TransformArgs};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate fonts, previews, and HTML pages (default).
+    Build(BuildOptions),
+    /// Make a font that has only the glyphs a game uses.
+    Subset(SubsetArgs),
+    /// Derive a new font by applying a transformation to glyphs.
+    Transform(TransformArgs),
+    /// Convert a BDF, Adafruit GFX, or U8g2 font into FFF.
+    Import(ImportArgs),
+    /// Re-pack a PNG sprite sheet into the canonical atlas.
+    Sheet(SheetArgs),
+    /// Export a font into BDF or a PNG sprite sheet.
+    Export(ExportArgs),
+    /// Generate a Rust crate that embeds fonts, a feature per family.
+    Codegen(CodegenArgs),
+    /// Render a mock game screen with the font, at 1x and scaled.
+    Device(DeviceArgs),
+    /// Serve the site on localhost and rebuild it on changes.
+    Serve(ServeArgs),
+}
 
 fn main() {
-    if let Err(err) = run() {
+    let cli = Cli::parse();
+    let res = match &cli.command {
+        None => run(&BuildOptions::default()),
+        Some(Command::Build(opts)) => run(opts),
+        Some(Command::Subset(args)) => cmd_subset(args),
+        Some(Command::Transform(args)) => cmd_transform(args),
+        Some(Command::Import(args)) => cmd_import(args),
+        Some(Command::Sheet(args)) => cmd_sheet(args),
+        Some(Command::Export(args)) => cmd_export(args),
+        Some(Command::Codegen(args)) => cmd_codegen(args),
+        Some(Command::Device(args)) => cmd_device(args),
+        Some(Command::Serve(args)) => cmd_serve(args),
+    };
+    if let Err(err) = res {
         println!("{err:?}");
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
-    let root = PathBuf::new().join("public");
-    std::fs::create_dir_all(&root).context("create public dir")?;
+fn run(opts: &BuildOptions) -> Result<()> {
+    let (config, manifest) = load_config(opts).context("load config")?;
+    // Building the catalog takes most of a no-op build, so it's skipped too.
+    let inputs = config.input_hash(&manifest).context("hash inputs")?;
+    if BuildCache::load(&config.output).is_unchanged(inputs) {
+        println!("Nothing changed since the last build");
+        return Ok(());
+    }
+    let fonts = build_catalog(&manifest, &config).context("build catalog")?;
+    build_site(&config, &fonts, inputs)
+}
+
+/// Generate fonts, images, and HTML pages for the given catalog.
+///
+/// The hash of inputs the catalog is built from is saved for [`run`]
+/// to skip the next build if nothing changes.
+pub(crate) fn build_site(config: &Config, fonts: &[Font], inputs: u64) -> Result<()> {
+    let root = &config.output;
+    std::fs::create_dir_all(root).context("create output dir")?;
     let fonts_path = root.join("fonts");
     let images_path = root.join("images");
+    let cache = BuildCache::load(root);
+    let licenses = collect_licenses(fonts, &config.licenses).context("check licenses")?;
 
     std::fs::create_dir_all(&fonts_path).context("create fonts dir")?;
-    let count = save_all_fonts(&fonts_path).context("generate fonts")?;
-    println!("Generated {count} fonts");
+    let updated = save_all_fonts(&fonts_path, fonts, &cache).context("generate fonts")?;
+    println!("Generated {} fonts ({updated} updated)", fonts.len());
+
+    let updated = fonts_to_images(fonts, &images_path, &cache).context("generate images")?;
+    println!("Generated images ({updated} updated)");
+
+    let updated = screens_to_images(fonts, &images_path.join("screens"), &cache)
+        .context("generate screens")?;
+    println!("Generated screens ({updated} updated)");
+
+    let updated = devices_to_images(fonts, &images_path.join("device"), &cache)
+        .context("generate device previews")?;
+    println!("Generated device previews ({updated} updated)");
+
+    let updated = write_checksums(root, fonts, &cache).context("generate checksums")?;
+    println!("Generated checksums ({updated} updated)");
 
-    fonts_to_images(&fonts_path, &images_path).context("generate images")?;
-    println!("Generated images");
+    let updated = write_credits(root, fonts, &cache).context("generate credits")?;
+    println!("Generated credits ({updated} updated)");
 
-    build_html(&root).context("generate HTML")?;
-    println!("Generated HTML pages");
+    let updated = write_license_texts(&root.join("licenses"), &licenses, &cache)
+        .context("copy license texts")?;
+    println!("Copied license texts ({updated} updated)");
+
+    let archives_path = root.join("archives");
+    let updated = build_archives(&archives_path, &config.base_url, &licenses, fonts, &cache)
+        .context("generate archives")?;
+    println!("Generated archives ({updated} updated)");
+
+    let updated = build_html(config, fonts, &licenses, &cache).context("generate HTML")?;
+    println!("Generated HTML pages ({updated} updated)");
+
+    cache.save(Some(inputs)).context("save build cache")?;
     Ok(())
 }
+
+/// Re-render only HTML pages, for changes that don't affect anything else.
+pub(crate) fn build_pages(config: &Config, fonts: &[Font]) -> Result<()> {
+    let cache = BuildCache::load(&config.output);
+    let licenses = collect_licenses(fonts, &config.licenses).context("check licenses")?;
+    let updated = build_html(config, fonts, &licenses, &cache).context("generate HTML")?;
+    println!("Generated HTML pages ({updated} updated)");
+    cache.save_partial().context("save build cache")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    /// Contents of all files in the directory, recursively.
+    fn read_tree(root: &Path, dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                read_tree(root, &path, files);
+            } else {
+                let key = path
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                files.insert(key, std::fs::read(&path).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_reproducible_build() {
+        let (mut config, manifest) = load_config(&BuildOptions::default()).unwrap();
+        // Merged, extra, eg, and derived fonts but not the whole catalog, to keep it fast.
+        config.encodings = vec!["ascii".to_string(), "iso_8859_5".to_string()];
+        let mut outputs = Vec::new();
+        for i in 0..2 {
+            let root = std::env::temp_dir().join(format!("test_reproducible_build_{i}"));
+            _ = std::fs::remove_dir_all(&root);
+            config.output = root.clone();
+            let fonts = build_catalog(&manifest, &config).unwrap();
+            let inputs = config.input_hash(&manifest).unwrap();
+            build_site(&config, &fonts, inputs).unwrap();
+            let mut files = BTreeMap::new();
+            read_tree(&root, &root, &mut files);
+            outputs.push(files);
+        }
+        assert!(outputs[0].contains_key("SHA256SUMS"));
+        assert_eq!(
+            outputs[0].keys().collect::<Vec<_>>(),
+            outputs[1].keys().collect::<Vec<_>>()
+        );
+        for (path, content) in &outputs[0] {
+            assert!(outputs[1][path] == *content, "{path} differs");
+        }
+    }
+}
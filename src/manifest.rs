@@ -0,0 +1,106 @@
+use crate::config::BuildOptions;
+use crate::extra_fonts::{License, Provenance};
+use crate::transform::Transform;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// The catalog configuration loaded from `manifest.toml`.
+#[derive(Deserialize, Default)]
+pub(crate) struct Manifest {
+    /// Derived fonts to publish in the catalog.
+    #[serde(default, rename = "variant")]
+    pub variants: Vec<Variant>,
+    /// Fonts converted from BDF, Adafruit GFX, or U8g2 sources.
+    #[serde(default, rename = "import")]
+    pub imports: Vec<Import>,
+    /// Default build options, overridden by CLI flags.
+    #[serde(default)]
+    pub build: BuildOptions,
+}
+
+/// A font derived from all fonts of a family by applying a transformation.
+#[derive(Deserialize)]
+pub(crate) struct Variant {
+    pub family: String,
+    /// If specified, derive only fonts in this encoding.
+    pub encoding: Option<String>,
+    /// If specified, derive only fonts of this size, like "4x6".
+    pub size: Option<String>,
+    #[serde(flatten)]
+    pub transform: Transform,
+}
+
+impl Variant {
+    /// Check if the variant should be derived from the given font.
+    pub fn matches(&self, family: &str, encoding: &str, width: u32, height: u32) -> bool {
+        if self.family != family {
+            return false;
+        }
+        if let Some(e) = &self.encoding {
+            if e != encoding {
+                return false;
+            }
+        }
+        if let Some(size) = &self.size {
+            if *size != format!("{width}x{height}") {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The family name of the derived fonts.
+    pub fn family_name(&self) -> String {
+        match self.transform.suffix() {
+            Some(suffix) => format!("{}-{suffix}", self.family),
+            None => self.family.clone(),
+        }
+    }
+}
+
+/// A font converted from another format and published in the catalog.
+#[derive(Deserialize)]
+pub(crate) struct Import {
+    /// Path to the font source, relative to the manifest.
+    pub path: PathBuf,
+    pub family: String,
+    /// The encoding to convert the font into, or `unicode`.
+    pub encoding: String,
+    /// SPDX ID of the font license.
+    pub license: String,
+    /// Where the font comes from.
+    pub url: String,
+    /// The upstream license text, if the font has its own.
+    pub license_url: Option<String>,
+    /// The copyright notice that copies of the font must keep.
+    pub copyright: Option<String>,
+    pub author: String,
+    /// The upstream version or release date.
+    pub version: Option<String>,
+}
+
+impl Import {
+    pub fn license(&self) -> License {
+        License {
+            spdx: self.license.clone().into(),
+            url: self.license_url.clone().map(Cow::Owned),
+            copyright: self.copyright.clone().map(Cow::Owned),
+        }
+    }
+
+    pub fn provenance(&self) -> Provenance {
+        Provenance {
+            author: self.author.clone().into(),
+            url: self.url.clone().into(),
+            version: self.version.clone().map(Cow::Owned),
+            conversion: format!("converted from {}", self.path.display()).into(),
+        }
+    }
+}
+
+pub(crate) fn load_manifest(path: &Path) -> Result<Manifest> {
+    let raw = std::fs::read_to_string(path).context("read manifest")?;
+    toml::from_str(&raw).context("parse manifest")
+}
@@ -0,0 +1,101 @@
+use embedded_graphics::mono_font::mapping::StrGlyphMapping;
+
+// Glyph mappings for 8-bit encodings that embedded-graphics doesn't provide.
+//
+// Like in embedded-graphics mappings, the first glyph is the space (0x20)
+// but, unlike them, there is no gap: the glyph index is the byte value minus 0x20.
+// Undefined code points are mapped to the C1 control character with the same value
+// or, above 0x9F, to the replacement character (U+FFFD).
+
+/// KOI8-R, Russian.
+pub(crate) static KOI8_R: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\u{20}\u{7f}\u{2500}\u{2502}\u{250c}\u{2510}\u{2514}\u{2518}\u{251c}",
+        "\u{2524}\u{252c}\u{2534}\u{253c}\u{2580}\u{2584}\u{2588}\u{258c}\0\u{2590}",
+        "\u{2593}\u{2320}\u{25a0}\u{2219}\u{221a}\u{2248}\u{2264}\u{2265}\u{a0}",
+        "\u{2321}\u{b0}\u{b2}\u{b7}\u{f7}\0\u{2550}\u{2552}\u{451}\0\u{2553}\u{2561}",
+        "\u{401}\0\u{2562}\u{256c}\u{a9}\u{44e}\u{430}\u{431}\u{446}\u{434}\u{435}",
+        "\u{444}\u{433}\u{445}\0\u{438}\u{43f}\u{44f}\0\u{440}\u{443}\u{436}\u{432}",
+        "\u{44c}\u{44b}\u{437}\u{448}\u{44d}\u{449}\u{447}\u{44a}\u{42e}\u{410}",
+        "\u{411}\u{426}\u{414}\u{415}\u{424}\u{413}\u{425}\0\u{418}\u{41f}\u{42f}\0\u{420}",
+        "\u{423}\u{416}\u{412}\u{42c}\u{42b}\u{417}\u{428}\u{42d}\u{429}\u{427}",
+        "\u{42a}",
+    ),
+    0,
+);
+
+/// KOI8-U, Ukrainian.
+///
+/// Same as KOI8-R but with Ukrainian letters in place of some box-drawing characters.
+pub(crate) static KOI8_U: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\u{20}\u{7f}\u{2500}\u{2502}\u{250c}\u{2510}\u{2514}\u{2518}\u{251c}",
+        "\u{2524}\u{252c}\u{2534}\u{253c}\u{2580}\u{2584}\u{2588}\u{258c}\0\u{2590}",
+        "\u{2593}\u{2320}\u{25a0}\u{2219}\u{221a}\u{2248}\u{2264}\u{2265}\u{a0}",
+        "\u{2321}\u{b0}\u{b2}\u{b7}\u{f7}\0\u{2550}\u{2552}\u{451}\u{454}\u{2554}",
+        "\u{456}\u{457}\0\u{2557}\u{255b}\u{491}\0\u{255d}\u{2561}\u{401}\u{404}",
+        "\u{2563}\u{406}\u{407}\0\u{2566}\u{256a}\u{490}\u{256c}\u{a9}\u{44e}\u{430}",
+        "\u{431}\u{446}\u{434}\u{435}\u{444}\u{433}\u{445}\0\u{438}\u{43f}\u{44f}\0\u{440}",
+        "\u{443}\u{436}\u{432}\u{44c}\u{44b}\u{437}\u{448}\u{44d}\u{449}\u{447}",
+        "\u{44a}\u{42e}\u{410}\u{411}\u{426}\u{414}\u{415}\u{424}\u{413}\u{425}\0\u{418}",
+        "\u{41f}\u{42f}\0\u{420}\u{423}\u{416}\u{412}\u{42c}\u{42b}\u{417}\u{428}",
+        "\u{42d}\u{429}\u{427}\u{42a}",
+    ),
+    0,
+);
+
+/// Windows-1251, Cyrillic.
+pub(crate) static CP1251: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\u{20}\u{7f}\u{402}\u{403}\u{201a}\u{453}\u{201e}\u{2026}\u{2020}\u{2021}",
+        "\u{20ac}\u{2030}\u{409}\u{2039}\u{40a}\u{40c}\u{40b}\u{40f}\u{452}\u{2018}",
+        "\u{2019}\u{201c}\u{201d}\u{2022}\u{2013}\u{2014}\u{98}\u{2122}\u{459}",
+        "\u{203a}\u{45a}\u{45c}\u{45b}\u{45f}\u{a0}\u{40e}\u{45e}\u{408}\u{a4}\u{490}",
+        "\u{a6}\u{a7}\u{401}\u{a9}\u{404}\0\u{ab}\u{ae}\u{407}\u{b0}\u{b1}\u{406}",
+        "\u{456}\u{491}\0\u{b5}\u{b7}\u{451}\u{2116}\u{454}\u{bb}\u{458}\u{405}",
+        "\u{455}\u{457}\0\u{410}\u{44f}",
+    ),
+    0,
+);
+
+/// ISO-8859-8, Latin/Hebrew.
+///
+/// Hebrew letters are stored in logical order, like in Unicode.
+pub(crate) static ISO_8859_8: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\u{20}\u{a0}\u{fffd}\0\u{a2}\u{a9}\u{d7}\0\u{ab}\u{b9}\u{f7}\0\u{bb}",
+        "\u{be}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{2017}\0\u{5d0}\u{5ea}",
+        "\u{fffd}\u{fffd}\u{200e}\u{200f}\u{fffd}",
+    ),
+    0,
+);
+
+/// ISO-8859-6, Latin/Arabic.
+///
+/// Letters are the base (isolated) forms, without contextual shaping.
+pub(crate) static ISO_8859_6: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\u{20}\u{a0}\u{fffd}\u{fffd}\u{fffd}\u{a4}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{60c}\u{ad}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{61b}\u{fffd}\u{fffd}\u{fffd}\u{61f}\u{fffd}\0\u{621}\u{63a}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\0\u{640}\u{652}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+        "\u{fffd}\u{fffd}",
+    ),
+    0,
+);
+
+/// ISO-8859-11, Latin/Thai.
+///
+/// The same as TIS-620 plus the no-break space at 0xA0.
+pub(crate) static ISO_8859_11: StrGlyphMapping = StrGlyphMapping::new(
+    concat!(
+        "\0\u{20}\u{a0}\0\u{e01}\u{e3a}\u{fffd}\u{fffd}\u{fffd}\u{fffd}\0\u{e3f}",
+        "\u{e5b}\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+    ),
+    0,
+);
@@ -0,0 +1,418 @@
+use crate::bdf::{EgFile, MISC_LICENSE};
+use crate::cp437;
+use crate::extra_fonts::{Font, License, Provenance, EG_LICENSE, EG_PROVENANCE};
+use crate::fff::{Fff, UNICODE};
+use crate::fonts::FONTS;
+use crate::generator::{encoding_index, glyph_mapping};
+use anyhow::{bail, Result};
+use embedded_graphics::mono_font::{iso_8859_5, iso_8859_9, MonoFont};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+/// embedded-graphics fonts converted into FFF, shared by the whole build.
+///
+/// Most merges take glyphs from the same fonts, and the catalog publishes them too,
+/// so each font is converted only once.
+#[derive(Default)]
+pub(crate) struct Converted {
+    fonts: Mutex<HashMap<(usize, usize), Arc<Fff>>>,
+}
+
+impl Converted {
+    /// The font converted into the encoding with the given index.
+    pub fn get(&self, encoding_index: usize, font: &MonoFont) -> Arc<Fff> {
+        // Fonts are told apart by address: they are statics or live for the whole build.
+        let key = (encoding_index, font as *const MonoFont as usize);
+        if let Some(fff) = self.fonts.lock().unwrap().get(&key) {
+            return Arc::clone(fff);
+        }
+        let fff = Arc::new(Fff::from_mono(encoding_index, font));
+        self.fonts.lock().unwrap().insert(key, Arc::clone(&fff));
+        fff
+    }
+}
+
+/// A font composed from glyphs of several other fonts.
+pub(crate) struct Merge<'a> {
+    pub family: &'static str,
+    /// The target encoding or `unicode` for a Unicode-indexed font.
+    pub encoding: &'static str,
+    /// Where to take glyphs from.
+    ///
+    /// The first source covering a character wins. The first source also defines
+    /// the glyph size and the baseline, glyphs from other sources are aligned
+    /// by the baseline, centered horizontally, and padded or cropped to fit.
+    /// Box-drawing and block characters not covered by any source are drawn.
+    pub sources: Vec<Source<'a>>,
+}
+
+pub(crate) struct Source<'a> {
+    pub font: SourceFont<'a>,
+    /// Characters to take from this font.
+    pub chars: RangeInclusive<char>,
+    pub license: License,
+    pub provenance: Provenance,
+}
+
+pub(crate) enum SourceFont<'a> {
+    /// An embedded-graphics font and its encoding.
+    Mono(&'a MonoFont<'a>, &'static str),
+    /// A font already converted into FFF.
+    Fff(Arc<Fff>),
+}
+
+impl Merge<'_> {
+    /// Licenses of all the sources, without duplicates.
+    pub fn licenses(&self) -> Vec<License> {
+        let mut licenses: Vec<License> = Vec::new();
+        for source in &self.sources {
+            let license = source.license.clone();
+            if !licenses.contains(&license) {
+                licenses.push(license);
+            }
+        }
+        licenses
+    }
+
+    /// Where the sources come from, without duplicates.
+    pub fn provenance(&self) -> Vec<Provenance> {
+        let mut provenance: Vec<Provenance> = Vec::new();
+        for source in &self.sources {
+            let source = source.provenance.clone();
+            if !provenance.iter().any(|p| p.conversion == source.conversion) {
+                provenance.push(source);
+            }
+        }
+        provenance
+    }
+
+    pub fn merge(&self, converted: &Converted) -> Result<Fff> {
+        let mut fonts: Vec<(Arc<Fff>, &RangeInclusive<char>)> = Vec::new();
+        for source in &self.sources {
+            let font = match &source.font {
+                SourceFont::Mono(font, encoding) => {
+                    let Some(index) = encoding_index(encoding) else {
+                        bail!("unknown source encoding: {encoding}");
+                    };
+                    converted.get(index, font)
+                }
+                SourceFont::Fff(font) => Arc::clone(font),
+            };
+            fonts.push((font, &source.chars));
+        }
+        let Some((first, _)) = fonts.first() else {
+            bail!("merged font {} has no sources", self.family);
+        };
+        let (target_index, target_chars) = match encoding_index(self.encoding) {
+            Some(index) => {
+                let mapping = glyph_mapping(index as u8).unwrap();
+                (index as u8, mapping.chars().collect())
+            }
+            // Unicode-indexed fonts have all characters any of the sources has.
+            None if self.encoding == "unicode" => {
+                let mut chars: Vec<char> = fonts
+                    .iter()
+                    .flat_map(|(font, range)| {
+                        let chars = font.visible_glyphs().into_iter().map(|(c, _)| c);
+                        chars.filter(|c| range.contains(c)).collect::<Vec<_>>()
+                    })
+                    .collect();
+                chars.sort();
+                chars.dedup();
+                (UNICODE, chars)
+            }
+            None => bail!("unknown encoding: {}", self.encoding),
+        };
+
+        // For each glyph of the target encoding, find the source font
+        // and the glyph index in that font.
+        let mut glyphs = Vec::new();
+        for &c in &target_chars {
+            let source = fonts.iter().enumerate().find_map(|(s, (font, chars))| {
+                let glyph = font.glyph_index(c).filter(|_| chars.contains(&c))?;
+                Some((s, glyph))
+            });
+            let glyph = match source {
+                Some((s, glyph)) => Glyph::Source(s, glyph),
+                None => match cp437::drawn_code(c) {
+                    Some(code) => Glyph::Drawn(code),
+                    None => Glyph::Missing,
+                },
+            };
+            glyphs.push(glyph);
+        }
+
+        let mut merged = Fff::from_glyphs(first, glyphs.len(), |g, x, y| match glyphs[g] {
+            Glyph::Source(s, glyph) => {
+                let source = &fonts[s].0;
+                let dx = (source.width as i32 - first.width as i32) / 2;
+                let dy = source.baseline as i32 - first.baseline as i32;
+                source.pixel_checked(glyph, x as i32 + dx, y as i32 + dy)
+            }
+            Glyph::Drawn(code) => cp437::drawn_pixel(code, first.width, first.height, x, y),
+            Glyph::Missing => false,
+        });
+        merged.encoding = target_index;
+        if target_index == UNICODE {
+            merged.code_points = target_chars;
+        }
+        Ok(merged)
+    }
+}
+
+/// Where a glyph of the merged font comes from.
+#[derive(Copy, Clone)]
+enum Glyph {
+    /// The source index and the glyph index in that source.
+    Source(usize, usize),
+    /// The CP437 code of a drawn glyph.
+    Drawn(u8),
+    Missing,
+}
+
+/// Encodings for which embedded-graphics has no fonts.
+///
+/// The first source encoding is the closest one, other encodings are tried
+/// in the same order as in the catalog. Characters that no eg encoding has
+/// are taken from the misc-fixed files of the eg fonts (see [`EgFile`]).
+static REMAPS: &[(&str, &str)] = &[
+    ("koi8_r", "iso_8859_5"),
+    ("koi8_u", "iso_8859_5"),
+    ("cp1251", "iso_8859_5"),
+];
+
+/// Fonts with glyphs for non-ASCII characters borrowed from embedded-graphics fonts.
+pub(crate) fn get_merges<'a>(fonts: &'a [Font<'a>], eg_files: &[EgFile]) -> Vec<Merge<'a>> {
+    let pico8 = fonts.iter().find(|f| f.family == "pico8").unwrap();
+    let ascii = ' '..='~';
+    let all = '\0'..=char::MAX;
+    let pico8_source = || Source {
+        font: SourceFont::Mono(&pico8.font, "ascii"),
+        chars: ascii.clone(),
+        license: pico8.license.clone(),
+        provenance: pico8.provenance.clone(),
+    };
+    let mut merges = vec![
+        Merge {
+            family: "pico8",
+            encoding: "iso_8859_5",
+            sources: vec![
+                pico8_source(),
+                Source {
+                    font: SourceFont::Mono(&iso_8859_5::FONT_4X6, "iso_8859_5"),
+                    chars: all.clone(),
+                    license: EG_LICENSE.clone(),
+                    provenance: EG_PROVENANCE.clone(),
+                },
+            ],
+        },
+        Merge {
+            family: "pico8",
+            encoding: "iso_8859_9",
+            sources: vec![
+                pico8_source(),
+                Source {
+                    font: SourceFont::Mono(&iso_8859_9::FONT_4X6, "iso_8859_9"),
+                    chars: all.clone(),
+                    license: EG_LICENSE.clone(),
+                    provenance: EG_PROVENANCE.clone(),
+                },
+            ],
+        },
+    ];
+    for (encoding, closest) in REMAPS {
+        let (_, eg_fonts) = FONTS.iter().find(|(e, _)| e == closest).unwrap();
+        for (i, (family, _)) in eg_fonts.iter().enumerate() {
+            let mut sources = eg_sources(closest, i, all.clone());
+            sources.extend(file_sources(eg_files, closest, i));
+            merges.push(Merge {
+                family,
+                encoding,
+                sources,
+            });
+        }
+        let mut sources = vec![pico8_source()];
+        sources.extend(eg_sources(closest, 0, all.clone()));
+        sources.extend(file_sources(eg_files, closest, 0));
+        merges.push(Merge {
+            family: "pico8",
+            encoding,
+            sources,
+        });
+    }
+
+    // All characters of all eg encodings in one Unicode-indexed font for each size and style.
+    let (_, latin) = FONTS.iter().find(|(e, _)| *e == "iso_8859_1").unwrap();
+    let (_, katakana) = FONTS.iter().find(|(e, _)| *e == "jis_x0201").unwrap();
+    for (i, (family, font)) in latin.iter().enumerate() {
+        let mut sources = eg_sources("iso_8859_1", i, all.clone());
+        // Katakana fonts come only in the regular style.
+        let size = font.character_size;
+        let katakana = katakana
+            .iter()
+            .find(|(f, font)| f == family && font.character_size == size);
+        if let Some((_, font)) = katakana {
+            sources.push(Source {
+                font: SourceFont::Mono(font, "jis_x0201"),
+                chars: all.clone(),
+                license: EG_LICENSE.clone(),
+                provenance: EG_PROVENANCE.clone(),
+            });
+        }
+        merges.push(Merge {
+            family,
+            encoding: "unicode",
+            sources,
+        });
+    }
+    merges
+}
+
+/// The embedded-graphics font with the given position in each of the encodings.
+///
+/// The font in the closest encoding goes first. Lists of fonts for all Latin, Cyrillic,
+/// and Greek encodings have the same sizes and styles in the same order.
+/// Other lists (katakana) have fewer fonts and are skipped.
+fn eg_sources(closest: &str, index: usize, chars: RangeInclusive<char>) -> Vec<Source<'static>> {
+    let (_, closest_fonts) = FONTS.iter().find(|(e, _)| *e == closest).unwrap();
+    let mut encodings: Vec<_> = FONTS.iter().filter(|(e, _)| *e == closest).collect();
+    encodings.extend(FONTS.iter().filter(|(e, _)| *e != closest));
+    encodings
+        .into_iter()
+        .filter(|(_, fonts)| fonts.len() == closest_fonts.len())
+        .map(|(encoding, fonts)| Source {
+            font: SourceFont::Mono(&fonts[index].1, encoding),
+            chars: chars.clone(),
+            license: EG_LICENSE.clone(),
+            provenance: EG_PROVENANCE.clone(),
+        })
+        .collect()
+}
+
+/// Misc-fixed files of the embedded-graphics font with the given position in the encoding.
+///
+/// The file of the same family goes first, then the regular one of the same size.
+fn file_sources(files: &[EgFile], encoding: &str, index: usize) -> Vec<Source<'static>> {
+    let (_, fonts) = FONTS.iter().find(|(e, _)| *e == encoding).unwrap();
+    let (family, font) = &fonts[index];
+    let size = font.character_size;
+    let mut files: Vec<&EgFile> = files
+        .iter()
+        .filter(|f| f.family == *family || f.family == "eg")
+        .filter(|f| f.data.width == size.width && f.data.height == size.height)
+        .collect();
+    files.sort_by_key(|f| f.family != *family);
+    files
+        .into_iter()
+        .map(|file| Source {
+            font: SourceFont::Fff(Arc::clone(&file.data)),
+            chars: '\0'..=char::MAX,
+            license: MISC_LICENSE.clone(),
+            provenance: file.provenance.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bdf::eg_files;
+    use crate::extra_fonts::{get_fonts, load_atlases};
+    use embedded_graphics::mono_font::jis_x0201;
+    use embedded_graphics::mono_font::mapping::{GlyphMapping, ISO_8859_5};
+    use std::path::Path;
+
+    #[test]
+    fn test_merge() {
+        let atlases = load_atlases(Path::new("atlas")).unwrap();
+        let fonts = get_fonts(&atlases);
+        let eg_files = eg_files(Path::new("bdf")).unwrap();
+        let merges = get_merges(&fonts, &eg_files);
+        let merge = &merges[0];
+        let merged = merge.merge(&Converted::default()).unwrap();
+        assert_eq!(merged.encoding, 11);
+        assert_eq!(merged.glyph_count(), 192);
+        assert_eq!(merge.licenses().len(), 2);
+
+        let pico8 = fonts.iter().find(|f| f.family == "pico8").unwrap();
+        let pico8 = Fff::from_mono(0, &pico8.font);
+        let eg = Fff::from_mono(11, &iso_8859_5::FONT_4X6);
+        let ascii = ISO_8859_5.index('A');
+        let cyrillic = ISO_8859_5.index('Ж');
+        // The eg baseline is one pixel higher, so its glyphs are shifted down.
+        assert_eq!(eg.baseline + 1, merged.baseline);
+        for x in 0..merged.width {
+            assert!(!merged.pixel(cyrillic, x, 0));
+            for y in 0..merged.height {
+                assert_eq!(merged.pixel(ascii, x, y), pico8.pixel(ascii, x, y));
+            }
+            for y in 1..merged.height {
+                assert_eq!(merged.pixel(cyrillic, x, y), eg.pixel(cyrillic, x, y - 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_remaps_have_all_glyphs() {
+        let atlases = load_atlases(Path::new("atlas")).unwrap();
+        let fonts = get_fonts(&atlases);
+        let eg_files = eg_files(Path::new("bdf")).unwrap();
+        let converted = Converted::default();
+        for merge in get_merges(&fonts, &eg_files) {
+            if !REMAPS
+                .iter()
+                .any(|(encoding, _)| *encoding == merge.encoding)
+            {
+                continue;
+            }
+            let font = merge.merge(&converted).unwrap();
+            for (c, glyph) in font.visible_glyphs() {
+                let drawn =
+                    (0..font.height).any(|y| (0..font.width).any(|x| font.pixel(glyph, x, y)));
+                assert!(
+                    drawn || c.is_whitespace(),
+                    "{} {}x{} in {} has an empty glyph for {c}",
+                    merge.family,
+                    font.width,
+                    font.height,
+                    merge.encoding,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unicode_katakana() {
+        let atlases = load_atlases(Path::new("atlas")).unwrap();
+        let fonts = get_fonts(&atlases);
+        let eg_files = eg_files(Path::new("bdf")).unwrap();
+        let converted = Converted::default();
+        let font = get_merges(&fonts, &eg_files)
+            .iter()
+            .filter(|m| m.family == "eg" && m.encoding == "unicode")
+            .map(|m| m.merge(&converted).unwrap())
+            .find(|f| f.width == 6 && f.height == 13)
+            .unwrap();
+        let parsed = Fff::parse(&font.to_bytes().unwrap()).unwrap();
+        // Halfwidth katakana are far past the other code points, in a range of their own.
+        let katakana = '\u{ff61}'..='\u{ff9f}';
+        let count = parsed
+            .code_points
+            .iter()
+            .filter(|&&c| katakana.contains(&c))
+            .count();
+        assert_eq!(count, 63);
+        assert_eq!(parsed.code_points.last(), Some(&'\u{ff9f}'));
+
+        let index = encoding_index("jis_x0201").unwrap();
+        let jis = Fff::from_mono(index, &jis_x0201::FONT_6X13);
+        let glyph = parsed.glyph_index('ｱ').unwrap();
+        let jis_glyph = jis.glyph_index('ｱ').unwrap();
+        for y in 0..parsed.height {
+            for x in 0..parsed.width {
+                assert_eq!(parsed.pixel(glyph, x, y), jis.pixel(jis_glyph, x, y));
+            }
+        }
+    }
+}
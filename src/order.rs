@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use embedded_graphics::mono_font::mapping::GlyphMapping;
+use std::path::Path;
+
+/// The default glyph order: ASCII 0x20-0x7F, 16 characters per line.
+static ASCII: &str = include_str!("../ascii.txt");
+
+/// The order of glyphs in an atlas or a sprite sheet.
+///
+/// It's read from a text file like `ascii.txt`: characters of the glyphs
+/// row by row, one line per row.
+pub(crate) struct GlyphOrder {
+    /// The characters in the order of cells. `None` is an unused cell.
+    pub chars: Vec<Option<char>>,
+    /// The number of columns, if the order is laid out in rows.
+    pub columns: Option<u32>,
+    /// The cell to use for characters the order doesn't have.
+    replacement: usize,
+}
+
+impl GlyphOrder {
+    pub fn read(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path).context("read glyph order")?;
+        Ok(Self::parse(&raw))
+    }
+
+    /// The order of the canonical atlas, the same as in `ascii.txt`.
+    ///
+    /// The file has `?` in the last cell to stay printable, the cell is DEL.
+    pub fn ascii() -> Self {
+        let mut order = Self::parse(ASCII);
+        order.chars[0x7F - 0x20] = Some('\x7F');
+        order
+    }
+
+    /// Parse a glyph order file.
+    ///
+    /// Lines shorter than the longest one are padded with unused cells.
+    /// A single line gives the order without the layout.
+    pub fn parse(raw: &str) -> Self {
+        let lines: Vec<Vec<char>> = raw
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().collect())
+            .collect();
+        if lines.len() == 1 {
+            return Self::flat(&lines[0]);
+        }
+        let columns = lines.iter().map(Vec::len).max().unwrap_or(0);
+        let mut chars = Vec::new();
+        for line in lines {
+            let padding = columns - line.len();
+            chars.extend(line.into_iter().map(Some));
+            chars.extend(std::iter::repeat_n(None, padding));
+        }
+        Self::new(chars, Some(columns as u32))
+    }
+
+    /// The order of the given characters, without the layout.
+    pub fn flat(chars: &[char]) -> Self {
+        Self::new(chars.iter().copied().map(Some).collect(), None)
+    }
+
+    /// Missing characters are drawn as a blank cell: an unused one or the space.
+    /// If there are none, the index is past the last cell, which is blank too.
+    fn new(chars: Vec<Option<char>>, columns: Option<u32>) -> Self {
+        let replacement = chars
+            .iter()
+            .position(|c| matches!(c, None | Some(' ')))
+            .unwrap_or(chars.len());
+        Self {
+            chars,
+            columns,
+            replacement,
+        }
+    }
+
+    /// The cell with the glyph for the character.
+    ///
+    /// Orders without lowercase letters use uppercase ones instead.
+    pub fn position(&self, c: char) -> Option<usize> {
+        let find = |c| self.chars.iter().position(|&o| o == Some(c));
+        find(c).or_else(|| {
+            let mut upper = c.to_uppercase();
+            match (upper.next(), upper.next()) {
+                (Some(u), None) if u != c => find(u),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// The glyph order file for the characters, 16 per line.
+pub(crate) fn order_text(chars: &[char]) -> String {
+    let mut text = String::new();
+    for line in chars.chunks(16) {
+        text.extend(line);
+        text.push('\n');
+    }
+    text
+}
+
+impl GlyphMapping for GlyphOrder {
+    fn index(&self, c: char) -> usize {
+        self.position(c).unwrap_or(self.replacement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let order = GlyphOrder::ascii();
+        assert_eq!(order.columns, Some(16));
+        assert_eq!(order.chars.len(), 96);
+        assert_eq!(order.index('A'), 0x41 - 0x20);
+        assert_eq!(order.index('?'), 0x3F - 0x20);
+        assert_eq!(order.index('\x7F'), 0x7F - 0x20);
+
+        let order = GlyphOrder::parse("ABC\n0123\n");
+        assert_eq!(order.columns, Some(4));
+        assert_eq!(order.chars[3], None);
+        assert_eq!(order.index('b'), 1);
+        assert_eq!(order.index('2'), 6);
+        // The unused cell after "C".
+        assert_eq!(order.index('?'), 3);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::catalog::{build_catalog, Font};
+use crate::config::{files_in, load_config, BuildOptions, Config};
+use crate::{build_pages, build_site};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tiny_http::{Header, Request, Response, Server};
+
+/// Injected into all served HTML pages to reload the page after each rebuild.
+const RELOAD_SCRIPT: &str = r#"<script>
+  (() => {
+    let version = null;
+    setInterval(async () => {
+      try {
+        const resp = await fetch("/__version");
+        const newVersion = await resp.text();
+        if (version !== null && newVersion !== version) location.reload();
+        version = newVersion;
+      } catch (e) {}
+    }, 500);
+  })();
+</script>"#;
+
+#[derive(clap::Args)]
+pub(crate) struct ServeArgs {
+    /// The port to listen on.
+    #[arg(long, default_value_t = 8000)]
+    port: u16,
+
+    #[command(flatten)]
+    build: BuildOptions,
+}
+
+/// Serve the site on localhost and rebuild it when inputs change.
+pub(crate) fn cmd_serve(args: &ServeArgs) -> Result<()> {
+    let (mut config, manifest) = load_config(&args.build).context("load config")?;
+    let mut fonts = build_catalog(&manifest, &config).context("build catalog")?;
+    build_site(&config, &fonts, config.input_hash(&manifest)?)?;
+
+    let addr = ("127.0.0.1", args.port);
+    let server = Server::http(addr).map_err(|err| anyhow::anyhow!("start server: {err}"))?;
+    let version = Arc::new(AtomicU64::new(0));
+    {
+        let root = config.output.clone();
+        let version = Arc::clone(&version);
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                if let Err(err) = handle(request, &root, &version) {
+                    println!("{err:?}");
+                }
+            }
+        });
+    }
+    println!("Serving on http://localhost:{}/", args.port);
+
+    // Changes in templates require only re-rendering HTML pages
+    // but changes in atlases or the manifest require rebuilding the catalog.
+    let mut templates = snapshot(&[&config.templates]);
+    let mut sources = snapshot(&[
+        &config.atlas,
+        &config.raw_atlas,
+        &config.bdf,
+        &config.manifest,
+    ]);
+    loop {
+        std::thread::sleep(Duration::from_millis(300));
+        let new_templates = snapshot(&[&config.templates]);
+        let new_sources = snapshot(&[
+            &config.atlas,
+            &config.raw_atlas,
+            &config.bdf,
+            &config.manifest,
+        ]);
+        if new_templates == templates && new_sources == sources {
+            continue;
+        }
+        let res = if new_sources != sources {
+            println!("Sources changed, rebuilding...");
+            reload(&args.build, &mut config, &mut fonts)
+        } else {
+            println!("Templates changed, rebuilding pages...");
+            build_pages(&config, &fonts)
+        };
+        templates = new_templates;
+        sources = new_sources;
+        match res {
+            Ok(()) => _ = version.fetch_add(1, Ordering::Relaxed),
+            Err(err) => println!("{err:?}"),
+        }
+    }
+}
+
+/// Reload the manifest and rebuild the catalog.
+///
+/// The served directory stays the same even if the output path in the manifest changes.
+fn reload(opts: &BuildOptions, config: &mut Config, fonts: &mut Vec<Font>) -> Result<()> {
+    let (mut new_config, manifest) = load_config(opts).context("load config")?;
+    new_config.output = config.output.clone();
+    *fonts = build_catalog(&manifest, &new_config).context("build catalog")?;
+    *config = new_config;
+    build_site(config, fonts, config.input_hash(&manifest)?)
+}
+
+/// Paths, modification times, and sizes of all files in the given directories
+/// and their subdirectories.
+fn snapshot(paths: &[&Path]) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let mut result = Vec::new();
+    for path in paths {
+        let entries: Vec<PathBuf> = if path.is_dir() {
+            files_in(path)
+        } else {
+            vec![path.to_path_buf()]
+        };
+        for path in entries {
+            if let Ok(meta) = std::fs::metadata(&path) {
+                result.push((path, meta.modified().ok(), meta.len()));
+            }
+        }
+    }
+    result.sort();
+    result
+}
+
+fn handle(request: Request, root: &Path, version: &AtomicU64) -> Result<()> {
+    let url = request.url().split('?').next().unwrap_or("/");
+    // Escapes are decoded first, so that `%2e%2e` can't sneak past the `..` check.
+    let Some(url) = percent_decode(url) else {
+        return request.respond(not_found()).context("respond");
+    };
+    if url == "/__version" {
+        let version = version.load(Ordering::Relaxed).to_string();
+        return request
+            .respond(Response::from_string(version))
+            .context("respond");
+    }
+
+    let mut path = root.to_path_buf();
+    for part in url.split('/') {
+        if part == ".." || part.contains('\\') {
+            return request.respond(not_found()).context("respond");
+        }
+        if !part.is_empty() && part != "." {
+            path.push(part);
+        }
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    let Ok(mut body) = std::fs::read(&path) else {
+        return request.respond(not_found()).context("respond");
+    };
+
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let content_type = match ext {
+        "html" => "text/html; charset=utf-8",
+        "png" => "image/png",
+        "txt" => "text/plain; charset=utf-8",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    };
+    if ext == "html" {
+        let html = String::from_utf8_lossy(&body);
+        let html = html.replace("</body>", &format!("{RELOAD_SCRIPT}</body>"));
+        body = html.into_bytes();
+    }
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    let response = Response::from_data(body).with_header(header);
+    request.respond(response).context("respond")
+}
+
+fn not_found() -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string("not found").with_status_code(404)
+}
+
+/// Decode `%XX` escapes in the URL path. `None` if an escape or the result is invalid.
+fn percent_decode(url: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(url.len());
+    let mut rest = url.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|h| h.iter().all(u8::is_ascii_hexdigit))?;
+            let hex = std::str::from_utf8(hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("/a%20b.html").unwrap(), "/a b.html");
+        assert_eq!(percent_decode("/%2e%2E/x").unwrap(), "/../x");
+        assert_eq!(percent_decode("/%E2%82%AC").unwrap(), "/€");
+        assert_eq!(percent_decode("/%zz"), None);
+        assert_eq!(percent_decode("/%2"), None);
+        assert_eq!(percent_decode("/%+1"), None);
+        assert_eq!(percent_decode("/%FF"), None);
+    }
+}
@@ -0,0 +1,499 @@
+use crate::export::SheetMetrics;
+use crate::image::write_bitmap;
+use crate::order::{order_text, GlyphOrder};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The largest margin and spacing tried by auto-detection.
+const MAX_GAP: u32 = 4;
+
+/// Characters of the canonical atlas, 16 per row, like in `ascii.txt`.
+const CANONICAL: std::ops::RangeInclusive<char> = ' '..='\x7f';
+
+#[derive(clap::Args)]
+pub(crate) struct SheetArgs {
+    /// The sprite sheet to convert (PNG).
+    sheet: PathBuf,
+
+    /// Where to save the canonical atlas (PNG).
+    output: PathBuf,
+
+    /// A file with the characters of the sheet, one line per row (like `ascii.txt`).
+    ///
+    /// A single line means the number of columns is detected.
+    /// [default: the text file with the same name as the sheet, if any,
+    /// or printable ASCII in any number of columns]
+    #[arg(long)]
+    order: Option<PathBuf>,
+
+    /// The size of a glyph cell, like `8x8` [default: detected].
+    #[arg(long, value_parser = parse_size)]
+    cell: Option<(u32, u32)>,
+
+    /// The gap between cells in pixels [default: detected].
+    #[arg(long)]
+    spacing: Option<u32>,
+
+    /// The gap between the sheet edges and the first cell in pixels [default: detected].
+    #[arg(long)]
+    margin: Option<u32>,
+
+    /// The background color, like `#000000` [default: the most common color].
+    #[arg(long, value_parser = parse_color)]
+    background: Option<Rgba>,
+
+    /// The glyph color. Pixels closer to it than to the background are glyph pixels
+    /// [default: the color most different from the background].
+    #[arg(long, value_parser = parse_color)]
+    foreground: Option<Rgba>,
+}
+
+type Rgba = [u8; 4];
+
+/// How glyphs are placed on the sheet.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Grid {
+    pub columns: u32,
+    pub rows: u32,
+    pub cell_width: u32,
+    pub cell_height: u32,
+    pub spacing: u32,
+    pub margin: u32,
+}
+
+/// What is known about the grid in advance. Everything else is detected.
+#[derive(Default)]
+pub(crate) struct Hints {
+    pub columns: Option<u32>,
+    pub cell: Option<(u32, u32)>,
+    pub spacing: Option<u32>,
+    pub margin: Option<u32>,
+}
+
+/// Glyph pixels of the sheet, with a summed-area table for counting them in rectangles.
+pub(crate) struct Mask {
+    width: u32,
+    height: u32,
+    /// The number of set pixels above and to the left, (width + 1) x (height + 1).
+    sums: Vec<u32>,
+    /// For each column, how many of its set pixels have a set pixel on the left.
+    joints_x: Vec<u32>,
+    /// For each row, how many of its set pixels have a set pixel above.
+    joints_y: Vec<u32>,
+}
+
+impl Mask {
+    pub fn new(width: u32, height: u32, pixel: impl Fn(u32, u32) -> bool) -> Self {
+        let stride = width as usize + 1;
+        let mut sums = vec![0; stride * (height as usize + 1)];
+        let mut joints_x = vec![0; width as usize + 1];
+        let mut joints_y = vec![0; height as usize + 1];
+        for y in 0..height {
+            let mut row = 0;
+            for x in 0..width {
+                let set = pixel(x, y);
+                row += u32::from(set);
+                let (x, y) = (x as usize, y as usize);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+                if set {
+                    joints_x[x] += u32::from(x > 0 && pixel(x as u32 - 1, y as u32));
+                    joints_y[y] += u32::from(y > 0 && pixel(x as u32, y as u32 - 1));
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            sums,
+            joints_x,
+            joints_y,
+        }
+    }
+
+    /// The number of set pixels in the rectangle, cropped by the sheet edges.
+    fn count(&self, x: u32, y: u32, width: u32, height: u32) -> u32 {
+        let stride = self.width as usize + 1;
+        let at = |x: u32, y: u32| self.sums[y as usize * stride + x as usize];
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        let right = (x + width).min(self.width);
+        let bottom = (y + height).min(self.height);
+        at(right, bottom) + at(x, y) - at(right, y) - at(x, bottom)
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> bool {
+        self.count(x, y, 1, 1) != 0
+    }
+}
+
+pub(crate) fn cmd_sheet(args: &SheetArgs) -> Result<()> {
+    let img = image::ImageReader::open(&args.sheet)
+        .context("open sheet")?
+        .decode()
+        .context("decode sheet")?
+        .to_rgba8();
+    let sibling = args.sheet.with_extension("txt");
+    let order = match &args.order {
+        Some(path) => GlyphOrder::read(path)?,
+        None if sibling.exists() => GlyphOrder::read(&sibling)?,
+        None => GlyphOrder::flat(&CANONICAL.collect::<Vec<_>>()),
+    };
+
+    // Fully transparent pixels are the same whatever their color.
+    let color = |x, y| match img.get_pixel(x, y).0 {
+        [_, _, _, 0] => [0; 4],
+        rgba => rgba,
+    };
+    let mut counts: HashMap<Rgba, usize> = HashMap::new();
+    for (x, y, _) in img.enumerate_pixels() {
+        *counts.entry(color(x, y)).or_default() += 1;
+    }
+    let background = match args.background {
+        Some(background) => background,
+        None => {
+            let most_common = counts.iter().max_by_key(|&(rgba, n)| (n, rgba));
+            most_common.map(|(&rgba, _)| rgba).unwrap_or_default()
+        }
+    };
+    // Other colors, like grid lines or outlines, are usually closer to the background.
+    let foreground = match args.foreground {
+        Some(foreground) => foreground,
+        None => {
+            let farthest = counts
+                .keys()
+                .max_by_key(|&&rgba| (distance(rgba, background), rgba));
+            farthest.copied().unwrap_or_default()
+        }
+    };
+    let mask = Mask::new(img.width(), img.height(), |x, y| {
+        let rgba = color(x, y);
+        distance(rgba, foreground) < distance(rgba, background)
+    });
+
+    let hints = Hints {
+        columns: order.columns,
+        cell: args.cell,
+        spacing: args.spacing,
+        margin: args.margin,
+    };
+    let grid = detect(&mask, &order, &hints)?;
+    println!(
+        "Detected {}x{} grid of {}x{} cells (spacing {}, margin {})",
+        grid.columns, grid.rows, grid.cell_width, grid.cell_height, grid.spacing, grid.margin,
+    );
+
+    // Sheets with only ASCII characters go in the canonical order,
+    // others keep their order, saved next to the atlas.
+    let chars: Vec<char> = order.chars.iter().flatten().copied().collect();
+    let target: Vec<char> = if chars.iter().all(|&c| CANONICAL.contains(&c)) {
+        CANONICAL.collect()
+    } else {
+        let mut target = Vec::new();
+        for c in chars {
+            if !target.contains(&c) {
+                target.push(c);
+            }
+        }
+        let order_path = args.output.with_extension("txt");
+        std::fs::write(&order_path, order_text(&target)).context("write glyph order")?;
+        println!("Saved the glyph order to {}", order_path.display());
+        target
+    };
+    let (data, width, height) = repack(&mask, &grid, &order, &target);
+    write_bitmap(&data, width, height, &args.output)?;
+    let missing: String = target
+        .iter()
+        .filter(|&&c| is_visible(c) && order.position(c).is_none())
+        .collect();
+    if !missing.is_empty() {
+        println!("Not on the sheet, left blank: {missing}");
+    }
+    // Metrics of an exported sheet, updated for the atlas layout.
+    if let Some(mut metrics) = SheetMetrics::read(&args.sheet)? {
+        metrics.columns = width / grid.cell_width;
+        metrics.rows = height / grid.cell_height;
+        metrics.spacing = 0;
+        metrics.margin = 0;
+        metrics.glyphs = target.len();
+        metrics.write(&args.output)?;
+    }
+    Ok(())
+}
+
+/// Find the grid that fits the sheet and the glyph order best.
+///
+/// A grid fits if all glyph pixels are inside of cells. See [`Fit`] for how
+/// the best one is chosen.
+pub(crate) fn detect(mask: &Mask, order: &GlyphOrder, hints: &Hints) -> Result<Grid> {
+    let total = mask.count(0, 0, mask.width, mask.height);
+    let glyphs = order.chars.len() as u32;
+    if glyphs == 0 {
+        bail!("glyph order is empty");
+    }
+    let gaps = |hint: Option<u32>| match hint {
+        Some(gap) => gap..=gap,
+        None => 0..=MAX_GAP,
+    };
+    let columns = match hints.columns {
+        Some(columns) => columns..=columns,
+        // With a known cell size, trailing columns may be empty.
+        None if hints.cell.is_some() => 1..=mask.width,
+        None => 1..=glyphs,
+    };
+
+    let mut best: Option<(Grid, Fit)> = None;
+    for columns in columns {
+        let needed = glyphs.div_ceil(columns);
+        let max_rows = if hints.cell.is_some() {
+            mask.height
+        } else {
+            needed + 2
+        };
+        for rows in needed..=max_rows {
+            for margin in gaps(hints.margin) {
+                for spacing in gaps(hints.spacing) {
+                    let Some((cell_width, cell_height)) =
+                        fit(mask, columns, rows, margin, spacing, hints.cell)
+                    else {
+                        continue;
+                    };
+                    let grid = Grid {
+                        columns,
+                        rows,
+                        cell_width,
+                        cell_height,
+                        spacing,
+                        margin,
+                    };
+                    let Some(fit) = score(mask, &grid, order, total) else {
+                        continue;
+                    };
+                    if best.as_ref().is_none_or(|(_, best)| fit > *best) {
+                        best = Some((grid, fit));
+                    }
+                }
+            }
+        }
+    }
+    match best {
+        Some((grid, _)) => Ok(grid),
+        None => bail!("no grid fits the sheet, try setting the cell size, spacing, and margin"),
+    }
+}
+
+/// The cell size of the grid with the given layout, if it fits the sheet.
+///
+/// The margin on the right and at the bottom can be larger by the spacing
+/// (when each cell is followed by a gap, including the last one).
+/// If the cell size is known, the last column and row may also be cropped.
+fn fit(
+    mask: &Mask,
+    columns: u32,
+    rows: u32,
+    margin: u32,
+    spacing: u32,
+    cell: Option<(u32, u32)>,
+) -> Option<(u32, u32)> {
+    let side = |size: u32, count: u32, cell: Option<u32>| {
+        if let Some(cell) = cell {
+            let extent = margin + count * (cell + spacing) - spacing;
+            let last = extent - cell;
+            return (last < size && extent + cell + spacing > size + margin).then_some(cell);
+        }
+        [margin, margin + spacing].into_iter().find_map(|end| {
+            let inner = (size + spacing).checked_sub(margin + end)?;
+            if !inner.is_multiple_of(count) || inner / count <= spacing {
+                return None;
+            }
+            Some(inner / count - spacing)
+        })
+    };
+    Some((
+        side(mask.width, columns, cell.map(|(width, _)| width))?,
+        side(mask.height, rows, cell.map(|(_, height)| height))?,
+    ))
+}
+
+/// How well a grid fits the sheet. Grids are compared field by field.
+#[derive(PartialEq, PartialOrd)]
+struct Fit {
+    /// Minus the number of connected pixels that cell boundaries go between.
+    /// Glyphs rarely touch their neighbors, so such a grid probably splits glyphs.
+    cuts: i64,
+    /// Cells matching the order (non-empty for visible characters, empty for the rest)
+    /// minus non-empty cells past the order.
+    matches: i64,
+    /// How many of the left and top cell edges have glyph pixels. Padding in fonts,
+    /// if any, is on the right and at the bottom, so blank left and top edges
+    /// mean that cells have taken in the gaps.
+    edges: i64,
+    /// Minus the spacing: blank padding stays in the cell rather than taken for gaps.
+    spacing: i64,
+    margin: i64,
+    /// Minus the distance from 16 columns.
+    columns: i64,
+}
+
+/// Evaluate the grid, or return `None` if some glyph pixels are outside of cells.
+fn score(mask: &Mask, grid: &Grid, order: &GlyphOrder, total: u32) -> Option<Fit> {
+    let mut inside = 0;
+    let mut matches = 0;
+    let (mut left, mut top) = (0, 0);
+    for row in 0..grid.rows {
+        for column in 0..grid.columns {
+            let (x, y) = grid.cell_origin(column, row);
+            let count = mask.count(x, y, grid.cell_width, grid.cell_height);
+            inside += count;
+            left += mask.count(x, y, 1, grid.cell_height);
+            top += mask.count(x, y, grid.cell_width, 1);
+            let index = (row * grid.columns + column) as usize;
+            match order.chars.get(index) {
+                Some(c) => {
+                    let visible = c.is_some_and(is_visible);
+                    matches += i64::from(visible == (count != 0));
+                }
+                None => matches -= i64::from(count != 0),
+            }
+        }
+    }
+    if inside != total {
+        return None;
+    }
+    let mut cuts = 0;
+    for column in 1..grid.columns {
+        let (x, _) = grid.cell_origin(column, 0);
+        cuts += mask.joints_x.get(x as usize).copied().unwrap_or(0);
+    }
+    for row in 1..grid.rows {
+        let (_, y) = grid.cell_origin(0, row);
+        cuts += mask.joints_y.get(y as usize).copied().unwrap_or(0);
+    }
+    Some(Fit {
+        cuts: -i64::from(cuts),
+        matches,
+        edges: i64::from(left != 0) + i64::from(top != 0),
+        spacing: -i64::from(grid.spacing),
+        margin: i64::from(grid.margin),
+        columns: -(i64::from(grid.columns) - 16).abs(),
+    })
+}
+
+impl Grid {
+    /// The top-left corner of the cell.
+    fn cell_origin(&self, column: u32, row: u32) -> (u32, u32) {
+        (
+            self.margin + column * (self.cell_width + self.spacing),
+            self.margin + row * (self.cell_height + self.spacing),
+        )
+    }
+}
+
+/// Copy glyphs into an atlas with 16 glyphs per row in the target order.
+///
+/// Returns 1-bit packed pixels and the size of the atlas.
+pub(crate) fn repack(
+    mask: &Mask,
+    grid: &Grid,
+    order: &GlyphOrder,
+    target: &[char],
+) -> (Vec<u8>, u32, u32) {
+    let (cell_width, cell_height) = (grid.cell_width, grid.cell_height);
+    let width = 16 * cell_width;
+    let height = (target.len() as u32).div_ceil(16) * cell_height;
+    let mut data = vec![0u8; (width * height).div_ceil(8) as usize];
+    for (index, &c) in target.iter().enumerate() {
+        let Some(position) = order.position(c) else {
+            continue;
+        };
+        let position = position as u32;
+        let (src_x, src_y) = grid.cell_origin(position % grid.columns, position / grid.columns);
+        let index = index as u32;
+        let (dst_x, dst_y) = (index % 16 * cell_width, index / 16 * cell_height);
+        for y in 0..cell_height {
+            for x in 0..cell_width {
+                if mask.pixel(src_x + x, src_y + y) {
+                    let bit = ((dst_y + y) * width + dst_x + x) as usize;
+                    data[bit / 8] |= 0x80 >> (bit % 8);
+                }
+            }
+        }
+    }
+    (data, width, height)
+}
+
+fn is_visible(c: char) -> bool {
+    !c.is_control() && !c.is_whitespace()
+}
+
+fn distance(a: Rgba, b: Rgba) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+fn parse_size(raw: &str) -> Result<(u32, u32)> {
+    let Some((width, height)) = raw.split_once('x') else {
+        bail!("expected WIDTHxHEIGHT, like 8x8");
+    };
+    Ok((
+        width.parse().context("parse width")?,
+        height.parse().context("parse height")?,
+    ))
+}
+
+fn parse_color(raw: &str) -> Result<Rgba> {
+    let hex = raw.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16).context("parse hex color")?;
+    match hex.len() {
+        6 => Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8, 0xFF]),
+        8 => Ok(value.to_be_bytes()),
+        _ => bail!("expected a color like #RRGGBB or #RRGGBBAA"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 grid of 3x4 cells with a 1px margin and spacing:
+    /// a space, two glyphs, and an unused cell.
+    const SHEET: &str = "\
+.........
+.....###.
+......#..
+......#..
+......#..
+.........
+.#.#.....
+.###.....
+.#.#.....
+.#.#.....
+.........
+";
+
+    #[test]
+    fn test_detect() {
+        let rows: Vec<&[u8]> = SHEET.lines().map(str::as_bytes).collect();
+        let mask = Mask::new(9, 11, |x, y| rows[y as usize][x as usize] == b'#');
+        let order = GlyphOrder::flat(&[' ', 'T', 'H']);
+        let grid = detect(&mask, &order, &Hints::default()).unwrap();
+        let expected = Grid {
+            columns: 2,
+            rows: 2,
+            cell_width: 3,
+            cell_height: 4,
+            spacing: 1,
+            margin: 1,
+        };
+        assert_eq!(grid, expected);
+
+        let target: Vec<char> = CANONICAL.collect();
+        let (data, width, height) = repack(&mask, &grid, &order, &target);
+        assert_eq!((width, height), (48, 24));
+        // "T" is 0x54 (row 3, column 4), "H" is 0x48 (row 2, column 8).
+        let pixel = |x: u32, y: u32| data[(y * width + x) as usize / 8] & (0x80 >> (x % 8)) != 0;
+        assert!(pixel(4 * 3, 3 * 4) && pixel(4 * 3 + 1, 3 * 4 + 3));
+        assert!(pixel(8 * 3, 2 * 4) && !pixel(8 * 3 + 1, 2 * 4));
+    }
+}
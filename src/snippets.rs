@@ -0,0 +1,93 @@
+use crate::catalog::Font;
+use crate::credits::credits;
+use anyhow::Result;
+use serde::Serialize;
+
+/// The text that snippets draw. It's ASCII, so it's the same in all encodings.
+const TEXT: &str = "Hello, world!";
+
+/// Code that loads the font and draws text with it, in one of the SDK languages.
+#[derive(Serialize)]
+pub(crate) struct Snippet {
+    pub language: &'static str,
+    /// The language name for ids and syntax highlighting.
+    pub slug: &'static str,
+    pub code: String,
+}
+
+/// Usage snippets for the font in all supported SDK languages.
+///
+/// The first one is the `firefly.toml` entry that puts the font file into the ROM
+/// under the name `font`, with the same path as on the site. The last one is
+/// the credits to ship with the game.
+pub(crate) fn snippets(font: &Font) -> Result<Vec<Snippet>> {
+    let path = format!("fonts/{}/{}.fff", font.encoding, font.name());
+    let size = font.data.to_bytes()?.len();
+    let note = match font.encoding {
+        "ascii" => String::new(),
+        "unicode" => "// The font is Unicode-indexed, the text is UTF-8.\n".to_string(),
+        encoding => {
+            format!("// The font is in {encoding}, other characters go as bytes 0x80-0xFF.\n")
+        }
+    };
+    Ok(vec![
+        Snippet {
+            language: "firefly.toml",
+            slug: "toml",
+            code: format!("[files]\nfont = {{ path = \"{path}\" }}\n"),
+        },
+        Snippet {
+            language: "Rust",
+            slug: "rust",
+            code: format!(
+                "let font = ff::load_file_buf(\"font\").unwrap();\n\
+                 {note}\
+                 ff::draw_text(\"{TEXT}\", &font.as_font(), ff::Point::new(10, 20), ff::Color::Black);\n",
+            ),
+        },
+        Snippet {
+            language: "Go",
+            slug: "go",
+            code: format!(
+                "font := firefly.LoadFile(\"font\", nil).Font()\n\
+                 {note}\
+                 firefly.DrawText(\"{TEXT}\", font, firefly.Point{{X: 10, Y: 20}}, firefly.ColorBlack)\n",
+            ),
+        },
+        Snippet {
+            language: "Zig",
+            slug: "zig",
+            code: format!(
+                "var buf: [{size}]u8 = undefined;\n\
+                 const font = ff.loadFile(\"font\", &buf).toFont();\n\
+                 {note}\
+                 ff.drawText(\"{TEXT}\", font, .{{ .x = 10, .y = 20 }}, .black);\n",
+            ),
+        },
+        Snippet {
+            language: "C",
+            slug: "c",
+            code: format!(
+                "static char buf[{size}];\n\
+                 File file = load_file(\"font\", buf, sizeof(buf));\n\
+                 Font font = (Font){{file.head, file.size}};\n\
+                 {note}\
+                 draw_text(\"{TEXT}\", font, (Point){{10, 20}}, BLACK);\n",
+            ),
+        },
+        Snippet {
+            language: "AssemblyScript",
+            slug: "assemblyscript",
+            code: format!(
+                "const font = ff.loadFile(\"font\");\n\
+                 {note}\
+                 ff.drawText(\"{TEXT}\", font, new ff.Point(10, 20), ff.Color.Black);\n",
+            ),
+        },
+        Snippet {
+            language: "CREDITS",
+            slug: "credits",
+            code: credits(&[font]),
+        },
+    ])
+}
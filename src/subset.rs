@@ -0,0 +1,116 @@
+use crate::fff::Fff;
+use crate::generator::glyph_mapping;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub(crate) struct SubsetArgs {
+    /// The font file to take glyphs from.
+    font: PathBuf,
+
+    /// Where to save the reduced font.
+    output: PathBuf,
+
+    /// Characters to keep.
+    #[arg(long, default_value = "")]
+    chars: String,
+
+    /// Keep all characters used in the given text file.
+    #[arg(long)]
+    strings: Option<PathBuf>,
+}
+
+pub(crate) fn cmd_subset(args: &SubsetArgs) -> Result<()> {
+    let mut chars: BTreeSet<char> = args.chars.chars().collect();
+    if let Some(path) = &args.strings {
+        let text = std::fs::read_to_string(path).context("read strings file")?;
+        chars.extend(text.chars().filter(|c| !c.is_control()));
+    }
+    if chars.is_empty() {
+        bail!("no characters to keep, use --chars or --strings");
+    }
+
+    let font = Fff::read(&args.font).context("read font")?;
+    if !font.mapping.is_empty() {
+        bail!("the font is already a subset");
+    }
+    if font.code_points.is_empty() && glyph_mapping(font.encoding).is_none() {
+        bail!("unknown encoding: {}", font.encoding);
+    }
+    let subset = subset_font(&font, &chars)?;
+    subset.write(&args.output)?;
+
+    let old_size = font.to_bytes()?.len();
+    let new_size = subset.to_bytes()?.len();
+    // The mapping table can outweigh the saved glyphs if almost all of them are kept.
+    let change = (new_size as i64 - old_size as i64) * 100 / old_size as i64;
+    let change = if change > 0 {
+        format!("{change}% larger")
+    } else {
+        format!("{}% smaller", -change)
+    };
+    println!(
+        "Kept {} of {} glyphs: {old_size} -> {new_size} bytes ({change})",
+        subset.mapping.len() + subset.code_points.len(),
+        font.glyph_count(),
+    );
+    Ok(())
+}
+
+/// Make a font that has only glyphs for the given characters.
+///
+/// Subsets of Unicode-indexed fonts are Unicode-indexed fonts with fewer code points.
+fn subset_font(font: &Fff, chars: &BTreeSet<char>) -> Result<Fff> {
+    let mut indices = BTreeSet::new();
+    for &c in chars {
+        let Some(index) = font.glyph_index(c) else {
+            bail!("character {c:?} is not in the font");
+        };
+        indices.insert(index);
+    }
+    let indices: Vec<usize> = indices.into_iter().collect();
+    if font.code_points.is_empty() && indices.len() > usize::from(u8::MAX) {
+        bail!("too many glyphs for a subset");
+    }
+
+    let mut subset = Fff::from_glyphs(font, indices.len(), |g, x, y| font.pixel(indices[g], x, y));
+    if font.code_points.is_empty() {
+        subset.mapping = indices.iter().map(|i| *i as u8).collect();
+    } else {
+        subset.code_points = indices.iter().map(|&i| font.code_points[i]).collect();
+    }
+    Ok(subset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fff::MAGIC_MAPPED;
+    use embedded_graphics::mono_font::ascii::FONT_4X6;
+
+    #[test]
+    fn test_subset_font() {
+        let font = Fff::from_mono(0, &FONT_4X6);
+        let chars: BTreeSet<char> = "Hi!H".chars().collect();
+        let subset = subset_font(&font, &chars).unwrap();
+        let raw = subset.to_bytes().unwrap();
+        assert_eq!(raw[0], MAGIC_MAPPED);
+        // The glyph count, then ASCII indices of '!', 'H', and 'i'.
+        assert_eq!(raw[7..11], [3, 0x01, 0x28, 0x49]);
+
+        let parsed = Fff::parse(&raw).unwrap();
+        assert_eq!(parsed.mapping, [0x01, 0x28, 0x49]);
+        assert!(parsed.glyph_count() >= 3);
+        assert!(parsed.glyph_count() < font.glyph_count());
+        assert_eq!(parsed.glyph_index('H'), Some(1));
+        assert_eq!(parsed.glyph_index('A'), None);
+        let h = font.glyph_index('H').unwrap();
+        for y in 0..font.height {
+            for x in 0..font.width {
+                assert_eq!(parsed.pixel(1, x, y), font.pixel(h, x, y));
+            }
+        }
+        assert!(subset_font(&font, &BTreeSet::from(['€'])).is_err());
+    }
+}
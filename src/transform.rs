@@ -0,0 +1,333 @@
+use crate::fff::{Fff, MAX_ATLAS_WIDTH, MAX_GLYPH_SIZE};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Deserializer};
+use std::path::PathBuf;
+
+/// A transformation producing a new font from an existing one.
+#[derive(Copy, Clone, Deserialize)]
+#[serde(tag = "transform", rename_all = "snake_case")]
+pub(crate) enum Transform {
+    /// Synthetic bold: each glyph smeared 1px to the right.
+    Bold,
+    /// 1px outline around each glyph, without the glyph itself.
+    Outline,
+    /// Each glyph with a copy shifted 1px to the bottom right.
+    Shadow,
+    /// Inverse video: set pixels are cleared and clear pixels are set.
+    Invert,
+    /// Integer upscaling, each pixel becomes a square of the given size.
+    Scale {
+        #[serde(deserialize_with = "deserialize_factor")]
+        factor: u32,
+    },
+    /// Glyphs rotated by 90 degrees, for vertical text.
+    Rotate { direction: Direction },
+}
+
+/// Scaling by 1 would produce a copy of the font with the same name.
+fn deserialize_factor<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let factor = u32::deserialize(deserializer)?;
+    if factor < 2 {
+        return Err(serde::de::Error::custom("scale factor must be at least 2"));
+    }
+    Ok(factor)
+}
+
+#[derive(Copy, Clone, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Direction {
+    /// Clockwise, the text reads from top to bottom.
+    Cw,
+    /// Counterclockwise, the text reads from bottom to top.
+    Ccw,
+}
+
+/// Transformations that can be selected from the CLI by name.
+#[derive(Copy, Clone, clap::ValueEnum)]
+enum Style {
+    Bold,
+    Outline,
+    Shadow,
+    Invert,
+}
+
+impl From<Style> for Transform {
+    fn from(style: Style) -> Self {
+        match style {
+            Style::Bold => Self::Bold,
+            Style::Outline => Self::Outline,
+            Style::Shadow => Self::Shadow,
+            Style::Invert => Self::Invert,
+        }
+    }
+}
+
+impl Transform {
+    /// The suffix added to the family name of the derived font.
+    ///
+    /// Scaled fonts keep the family name, they differ only in size.
+    pub fn suffix(self) -> Option<&'static str> {
+        match self {
+            Self::Bold => Some("bold"),
+            Self::Outline => Some("outline"),
+            Self::Shadow => Some("shadow"),
+            Self::Invert => Some("inverted"),
+            Self::Scale { .. } => None,
+            Self::Rotate {
+                direction: Direction::Cw,
+            } => Some("cw"),
+            Self::Rotate {
+                direction: Direction::Ccw,
+            } => Some("ccw"),
+        }
+    }
+
+    /// Check if the glyphs are rotated and so the font is for vertical text.
+    pub fn is_rotation(self) -> bool {
+        matches!(self, Self::Rotate { .. })
+    }
+
+    /// Human-readable description of the transformation.
+    pub fn label(self) -> String {
+        match self {
+            Self::Invert => "inverted".to_string(),
+            Self::Scale { factor } => format!("scaled {factor}x"),
+            Self::Rotate {
+                direction: Direction::Cw,
+            } => "rotated clockwise".to_string(),
+            Self::Rotate {
+                direction: Direction::Ccw,
+            } => "rotated counterclockwise".to_string(),
+            _ => self.suffix().unwrap().to_string(),
+        }
+    }
+
+    /// The glyph size of the derived font.
+    pub fn size(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Self::Bold => (width + 1, height),
+            Self::Outline => (width + 2, height + 2),
+            Self::Shadow => (width + 1, height + 1),
+            Self::Invert => (width, height),
+            Self::Scale { factor } => (width.saturating_mul(factor), height.saturating_mul(factor)),
+            Self::Rotate { .. } => (height, width),
+        }
+    }
+
+    /// The baseline of the derived font.
+    fn baseline(self, baseline: u32, height: u32) -> u32 {
+        match self {
+            Self::Outline => baseline + 1,
+            // The baseline is the last row of the glyph body,
+            // and that row becomes `factor` rows.
+            Self::Scale { factor } => (baseline + 1).saturating_mul(factor) - 1,
+            // The original baseline becomes vertical, so glyphs are aligned
+            // by the bottom of the cell.
+            Self::Rotate { .. } => height - 1,
+            _ => baseline,
+        }
+    }
+
+    /// Fails if the derived glyphs or atlas don't fit into the FFF header.
+    pub fn apply(self, font: &Fff) -> Result<Fff> {
+        let (width, height) = self.size(font.width, font.height);
+        let baseline = self.baseline(font.baseline, height);
+        if width > MAX_GLYPH_SIZE || height > MAX_GLYPH_SIZE || baseline > MAX_GLYPH_SIZE {
+            bail!("{} glyphs are {width}x{height}, at most {MAX_GLYPH_SIZE}x{MAX_GLYPH_SIZE} are supported", self.label());
+        }
+        let template = Fff {
+            encoding: font.encoding,
+            width,
+            height,
+            baseline,
+            atlas_width: 0,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        let count = font.glyph_count();
+        let mut derived = Fff::from_glyphs(&template, count, |g, x, y| {
+            let (x, y) = (x as i32, y as i32);
+            let get = |x, y| font.pixel_checked(g, x, y);
+            match self {
+                Self::Bold => get(x, y) || get(x - 1, y),
+                Self::Outline => {
+                    // Shift by 1px to make room for the outline on the top and left.
+                    let (x, y) = (x - 1, y - 1);
+                    if get(x, y) {
+                        return false;
+                    }
+                    (-1..=1).any(|dy| (-1..=1).any(|dx| get(x + dx, y + dy)))
+                }
+                Self::Shadow => get(x, y) || get(x - 1, y - 1),
+                Self::Invert => !get(x, y),
+                Self::Scale { factor } => {
+                    let factor = factor as i32;
+                    get(x / factor, y / factor)
+                }
+                Self::Rotate { direction } => match direction {
+                    Direction::Cw => get(y, font.height as i32 - 1 - x),
+                    Direction::Ccw => get(font.width as i32 - 1 - y, x),
+                },
+            }
+        });
+        if derived.atlas_width > MAX_ATLAS_WIDTH {
+            bail!(
+                "{} atlas is {} pixels wide, at most {MAX_ATLAS_WIDTH} are supported",
+                self.label(),
+                derived.atlas_width
+            );
+        }
+        derived.mapping = font.mapping.clone();
+        derived.code_points = font.code_points.clone();
+        Ok(derived)
+    }
+}
+
+#[derive(clap::Args)]
+pub(crate) struct TransformArgs {
+    /// The font file to transform.
+    font: PathBuf,
+
+    /// Where to save the derived font.
+    output: PathBuf,
+
+    /// The style transformation to apply.
+    #[arg(long, value_enum)]
+    style: Option<Style>,
+
+    /// Scale the font by the given integer factor, after applying the style.
+    #[arg(long)]
+    scale: Option<u32>,
+
+    /// Rotate glyphs by 90 degrees in the given direction, after scaling.
+    #[arg(long, value_enum)]
+    rotate: Option<Direction>,
+}
+
+pub(crate) fn cmd_transform(args: &TransformArgs) -> Result<()> {
+    let mut transforms: Vec<Transform> = Vec::new();
+    if let Some(style) = args.style {
+        transforms.push(style.into());
+    }
+    if let Some(factor) = args.scale {
+        if factor < 2 {
+            bail!("scale factor must be at least 2");
+        }
+        transforms.push(Transform::Scale { factor });
+    }
+    if let Some(direction) = args.rotate {
+        transforms.push(Transform::Rotate { direction });
+    }
+    if transforms.is_empty() {
+        bail!("nothing to do, use --style, --scale, or --rotate");
+    }
+
+    let font = Fff::read(&args.font).context("read font")?;
+    let mut derived = transforms[0].apply(&font)?;
+    for transform in &transforms[1..] {
+        derived = transform.apply(&derived)?;
+    }
+    derived.write(&args.output)?;
+    println!(
+        "{}x{} -> {}x{}",
+        font.width, font.height, derived.width, derived.height
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_font() -> Fff {
+        // A single glyph with a single pixel set in the middle.
+        let template = Fff {
+            encoding: 0,
+            width: 3,
+            height: 3,
+            baseline: 2,
+            atlas_width: 0,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        Fff::from_glyphs(&template, 1, |_, x, y| x == 1 && y == 1)
+    }
+
+    fn render(font: &Fff) -> Vec<String> {
+        (0..font.height)
+            .map(|y| {
+                (0..font.width)
+                    .map(|x| if font.pixel(0, x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_outline() {
+        let font = Transform::Outline.apply(&make_font()).unwrap();
+        assert_eq!(font.baseline, 3);
+        let expected = [".....", ".###.", ".#.#.", ".###.", "....."];
+        assert_eq!(render(&font), expected);
+    }
+
+    #[test]
+    fn test_scale() {
+        let font = Transform::Scale { factor: 2 }.apply(&make_font()).unwrap();
+        assert_eq!(font.baseline, 5);
+        let expected = ["......", "......", "..##..", "..##..", "......", "......"];
+        assert_eq!(render(&font), expected);
+    }
+
+    #[test]
+    fn test_scale_limits() {
+        assert!(Transform::Scale { factor: 100 }
+            .apply(&make_font())
+            .is_err());
+        let parse = |raw: &str| toml::from_str::<Transform>(raw);
+        assert!(parse("transform = \"scale\"\nfactor = 3").is_ok());
+        assert!(parse("transform = \"scale\"\nfactor = 1").is_err());
+        assert!(parse("transform = \"scale\"\nfactor = 0").is_err());
+    }
+
+    #[test]
+    fn test_rotate() {
+        // An L-shaped glyph.
+        let template = Fff {
+            encoding: 0,
+            width: 2,
+            height: 3,
+            baseline: 2,
+            atlas_width: 0,
+            mapping: Vec::new(),
+            code_points: Vec::new(),
+            atlas: Vec::new(),
+            metadata: Vec::new(),
+        };
+        let font = Fff::from_glyphs(&template, 1, |_, x, y| x == 0 || y == 2);
+
+        let cw = Transform::Rotate {
+            direction: Direction::Cw,
+        };
+        let rotated = cw.apply(&font).unwrap();
+        assert_eq!((rotated.width, rotated.height), (3, 2));
+        assert_eq!(rotated.baseline, 1);
+        assert_eq!(render(&rotated), ["###", "#.."]);
+
+        let ccw = Transform::Rotate {
+            direction: Direction::Ccw,
+        };
+        assert_eq!(render(&ccw.apply(&font).unwrap()), ["..#", "###"]);
+        assert_eq!(render(&ccw.apply(&rotated).unwrap()), render(&font));
+    }
+
+    #[test]
+    fn test_shadow() {
+        let font = Transform::Shadow.apply(&make_font()).unwrap();
+        assert_eq!(render(&font), ["....", ".#..", "..#.", "...."]);
+    }
+}
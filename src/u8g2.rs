@@ -0,0 +1,171 @@
+use crate::bitmap::{pack_rows, BitmapFont, Glyph};
+use crate::import::{initializer, parse_bytes};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+/// The size of the U8g2 font header.
+const HEADER_SIZE: usize = 23;
+
+/// Parse a U8g2 font source (comments already stripped).
+///
+/// The font is a `uint8_t` array, usually written as string literals:
+/// a 23-byte header with bit widths of glyph fields, then glyphs for
+/// characters 0-255 (1-byte code, 1-byte record size), then the Unicode section
+/// (a lookup table, then glyphs with a 2-byte code and 1-byte record size).
+/// Glyph bitmaps are run-length encoded.
+pub(crate) fn parse(src: &str) -> Result<BitmapFont> {
+    let init = initializer(src, "uint8_t").context("font array not found")?;
+    let data = parse_bytes(init).context("parse font array")?;
+    if data.len() < HEADER_SIZE {
+        bail!("font data is too short");
+    }
+    let header = Header {
+        bits_per_0: data[2],
+        bits_per_1: data[3],
+        bits_per_width: data[4],
+        bits_per_height: data[5],
+        bits_per_x: data[6],
+        bits_per_y: data[7],
+        bits_per_advance: data[8],
+    };
+    let unicode_start = HEADER_SIZE + usize::from(u16::from_be_bytes([data[21], data[22]]));
+
+    let mut glyphs = HashMap::new();
+    let mut pos = HEADER_SIZE;
+    while pos + 2 <= data.len() && data[pos + 1] != 0 {
+        let c = char::from(data[pos]);
+        glyphs.insert(c, header.decode(&data[pos + 2..])?);
+        pos += usize::from(data[pos + 1]);
+    }
+
+    // The first entry of the lookup table tells where the Unicode glyphs start.
+    if unicode_start + 2 <= data.len() {
+        let offset = u16::from_be_bytes([data[unicode_start], data[unicode_start + 1]]);
+        let mut pos = unicode_start + usize::from(offset);
+        while pos + 3 <= data.len() {
+            let code = u16::from_be_bytes([data[pos], data[pos + 1]]);
+            let size = usize::from(data[pos + 2]);
+            if code == 0 || size == 0 {
+                break;
+            }
+            if let Some(c) = char::from_u32(u32::from(code)) {
+                glyphs.insert(c, header.decode(&data[pos + 3..])?);
+            }
+            pos += size;
+        }
+    }
+    Ok(BitmapFont::new(glyphs))
+}
+
+/// Bit widths of glyph fields.
+struct Header {
+    bits_per_0: u8,
+    bits_per_1: u8,
+    bits_per_width: u8,
+    bits_per_height: u8,
+    bits_per_x: u8,
+    bits_per_y: u8,
+    bits_per_advance: u8,
+}
+
+impl Header {
+    fn decode(&self, data: &[u8]) -> Result<Glyph> {
+        let mut bits = Bits { data, pos: 0 };
+        let width = bits.unsigned(self.bits_per_width)?;
+        let height = bits.unsigned(self.bits_per_height)?;
+        let x_offset = bits.signed(self.bits_per_x)?;
+        let y_offset = bits.signed(self.bits_per_y)?;
+        let advance = bits.signed(self.bits_per_advance)?;
+
+        // Runs of background (a) and foreground (b) pixels. Each pair
+        // is followed by a bit telling if the same pair repeats.
+        let size = (width * height) as usize;
+        let mut pixels = vec![false; size];
+        let mut pos = 0;
+        while pos < size {
+            let a = bits.unsigned(self.bits_per_0)? as usize;
+            let b = bits.unsigned(self.bits_per_1)? as usize;
+            loop {
+                pos += a;
+                for pixel in pixels.iter_mut().skip(pos).take(b) {
+                    *pixel = true;
+                }
+                pos += b;
+                if bits.unsigned(1)? == 0 {
+                    break;
+                }
+            }
+        }
+        Ok(Glyph {
+            width,
+            height,
+            x_offset,
+            y_offset,
+            advance: advance.max(0) as u32,
+            rows: pack_rows(width, height, |i| pixels[i]),
+        })
+    }
+}
+
+/// Reads bit fields, least significant bit first.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Bits<'_> {
+    fn unsigned(&mut self, count: u8) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let Some(byte) = self.data.get(self.pos / 8) else {
+                bail!("glyph data is truncated");
+            };
+            if byte >> (self.pos % 8) & 1 != 0 {
+                value |= 1 << i;
+            }
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn signed(&mut self, count: u8) -> Result<i32> {
+        let value = self.unsigned(count)? as i32;
+        Ok(if count == 0 {
+            value
+        } else {
+            value - (1 << (count - 1))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "A" in the 8-bit section and "Ж" (with a descender) in the Unicode section.
+    const SOURCE: &str = concat!(
+        "const uint8_t u8g2_font_test[45] U8G2_FONT_SECTION(\"u8g2_font_test\") = \n",
+        "  \"\\2\\0\\2\\3\\3\\3\\2\\2\\4\\3\\3\\0\\377\\3\\1\\3\\1\\0\\0\\0\\0\\0\\10\"\n",
+        "  \"\\101\\6\\233\\162\\51\\2\\0\\0\\0\\4\\377\\377\\4\\26\\10\\223\\61\\121\\12\\0\\0\\0\";\n",
+    );
+
+    #[test]
+    fn test_parse() {
+        let font = parse(SOURCE).unwrap();
+        assert_eq!(font.glyphs.len(), 2);
+        assert!(font.is_fixed_cell());
+        assert_eq!((font.width, font.height, font.baseline()), (4, 4, 2));
+        let render = |c| {
+            let mut result = String::new();
+            for y in 0..font.height {
+                for x in 0..font.width {
+                    result.push(if font.pixel(c, x, y) { '#' } else { '.' });
+                }
+                result.push('\n');
+            }
+            result
+        };
+        assert_eq!(render('A'), ".#..\n#.#.\n###.\n....\n");
+        assert_eq!(render('Ж'), "....\n....\n#.#.\n.#..\n");
+    }
+}